@@ -0,0 +1,81 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, LitStr};
+
+/// Implémente sqlx_helpers::pagination::PaginatedRequest.
+/// Les champs lus sont `page` et `page_size`, de type i32 ou Option<i32>, renommables avec
+/// `#[pagination(page = "num_page", page_size = "limit")]`
+#[proc_macro_derive(PaginatedRequest, attributes(pagination))]
+pub fn derive_paginated_request(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut page = Ident::new("page", proc_macro2::Span::call_site());
+    let mut page_size = Ident::new("page_size", proc_macro2::Span::call_site());
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|x| x.path().is_ident("pagination"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let value: LitStr = meta.value()?.parse()?;
+
+            if meta.path.is_ident("page") {
+                page = value.parse()?;
+            } else if meta.path.is_ident("page_size") {
+                page_size = value.parse()?;
+            } else {
+                return Err(meta.error("expected `page` or `page_size`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "PaginatedRequest requires named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "PaginatedRequest can only be derived for structs",
+            ))
+        }
+    };
+
+    for name in [&page, &page_size] {
+        if !fields.iter().any(|x| x.ident.as_ref() == Some(name)) {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                format!("missing field `{name}`, see #[pagination(...)]"),
+            ));
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::sqlx_helpers::pagination::PaginatedRequest for #ident #type_generics #where_clause {
+            fn get_page(&self) -> ::std::option::Option<i32> {
+                ::std::convert::Into::into(self.#page)
+            }
+
+            fn get_page_size(&self) -> ::std::option::Option<i32> {
+                ::std::convert::Into::into(self.#page_size)
+            }
+        }
+    })
+}
@@ -0,0 +1,153 @@
+use crate::{dialect::Dialect, types::ValueType};
+
+/// Marque placée en tête du SQL rendu
+pub const DEBUG_MARKER: &str = "/* DEBUG ONLY - NOT FOR EXECUTION */ ";
+
+/// SQL de `builder.sql()` avec les valeurs liées écrites en littéraux, pour les logs.
+/// Le résultat est préfixé par DEBUG_MARKER et ne doit jamais être exécuté:
+/// l'échappement ne couvre que les cas courants et ne protège pas des injections.
+/// Les paramètres sans valeur correspondante sont laissés tels quels
+/// # Arguments
+/// * `sql` - requête avec ses paramètres ($1 sur Postgres, ? sinon)
+/// * `values` - valeurs liées, dans l'ordre
+pub fn render_sql<DB: Dialect>(sql: &str, values: &[ValueType]) -> String {
+    let mut result = String::from(DEBUG_MARKER);
+    let mut chars = sql.chars().peekable();
+    let mut quote: Option<char> = None;
+    let mut index = 0;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => {
+                quote = None;
+                result.push(c);
+            }
+            (Some(_), _) => result.push(c),
+            (None, '\'' | '"' | '`') => {
+                quote = Some(c);
+                result.push(c);
+            }
+            (None, '?') if !DB::NUMBERED_PARAMS => {
+                match values.get(index) {
+                    Some(value) => result.push_str(&DB::literal(value)),
+                    None => result.push(c),
+                }
+                index += 1;
+            }
+            (None, '$') if DB::NUMBERED_PARAMS => {
+                let mut digits = String::new();
+                while let Some(x) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(x);
+                }
+
+                let value = digits
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|x| values.get(x.checked_sub(1)?));
+                match value {
+                    Some(value) => result.push_str(&DB::literal(value)),
+                    None => {
+                        result.push(c);
+                        result.push_str(&digits);
+                    }
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// 'valeur' avec les apostrophes doublées
+pub(crate) fn quote_str(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Littéral identique sur les trois bases: nombres, dates et NULL.
+/// None pour les chaînes, booléens, octets, listes et JSON, écrits par chaque base
+pub(crate) fn common_literal(value: &ValueType) -> Option<String> {
+    let literal = match value {
+        ValueType::Int(x) => x.to_string(),
+        ValueType::BigInt(x) => x.to_string(),
+        ValueType::Float(x) if x.is_finite() => x.to_string(),
+        ValueType::Float(x) => quote_str(&x.to_string()),
+        ValueType::Double(x) if x.is_finite() => x.to_string(),
+        ValueType::Double(x) => quote_str(&x.to_string()),
+        #[cfg(feature = "decimal")]
+        ValueType::Decimal(x) => x.to_string(),
+        #[cfg(feature = "uuid")]
+        ValueType::Uuid(x) => quote_str(&x.to_string()),
+        ValueType::Date(x) => quote_str(&x.format("%Y-%m-%d").to_string()),
+        ValueType::Time(x) => quote_str(&x.format("%H:%M:%S%.f").to_string()),
+        ValueType::DateTime(x) => quote_str(&x.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+        ValueType::DateTimeUtc(x) => {
+            quote_str(&x.naive_utc().format("%Y-%m-%d %H:%M:%S%.f").to_string())
+        }
+        ValueType::Null | ValueType::None => "NULL".to_string(),
+        _ => return None,
+    };
+
+    Some(literal)
+}
+
+/// (a, b) pour les listes IN
+pub(crate) fn tuple_literal<DB: Dialect>(values: &[ValueType]) -> String {
+    let values = values.iter().map(DB::literal).collect::<Vec<_>>();
+    format!("({})", values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{types::chrono::NaiveDate, MySql, Postgres, QueryBuilder, Sqlite};
+
+    use super::*;
+    use crate::operations::SqlOperation;
+
+    #[test]
+    fn postgres() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let values = vec![
+            ValueType::String("O'Neil".to_string()),
+            date.into(),
+            ValueType::Bool(true),
+            ValueType::list(vec!["a", "b"]),
+        ];
+
+        assert_eq!(
+            render_sql::<Postgres>(
+                "SELECT '$1' FROM t WHERE name=$1 AND d>$2 AND active=$3 AND code = ANY($4) AND x=$5",
+                &values
+            ),
+            "/* DEBUG ONLY - NOT FOR EXECUTION */ SELECT '$1' FROM t WHERE name='O''Neil' AND d>'2024-05-01' AND active=TRUE AND code = ANY(ARRAY['a', 'b']) AND x=$5"
+        );
+    }
+
+    #[test]
+    fn mysql() {
+        let mut builder = QueryBuilder::<MySql>::new("SELECT * FROM t WHERE 1=1");
+        let values = vec![
+            ValueType::String(r"a\b".to_string()),
+            ValueType::Bytes(vec![0xab, 1]),
+            ValueType::Double(1.5),
+        ];
+        builder.push_value(" AND path=", values[0].clone());
+        builder.push_value(" AND hash=", values[1].clone());
+        builder.push_value(" AND amount>", values[2].clone());
+
+        assert_eq!(
+            render_sql::<MySql>(builder.sql(), &values),
+            r"/* DEBUG ONLY - NOT FOR EXECUTION */ SELECT * FROM t WHERE 1=1 AND path='a\\b' AND hash=X'ab01' AND amount>1.5"
+        );
+    }
+
+    #[test]
+    fn sqlite() {
+        let values = vec![ValueType::Bool(false), ValueType::Null];
+
+        assert_eq!(
+            render_sql::<Sqlite>("SELECT \"a?\" FROM t WHERE b=? AND c=? AND d=?", &values),
+            "/* DEBUG ONLY - NOT FOR EXECUTION */ SELECT \"a?\" FROM t WHERE b=0 AND c=NULL AND d=?"
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+
+use sqlx::{Pool, QueryBuilder};
+
+use crate::{
+    dialect::Dialect,
+    ident::Ident,
+    operations::{push_conditions, SqlOperation},
+};
+
+#[derive(Debug)]
+pub enum DeleteError {
+    /// aucun filtre n'a été écrit: la requête viderait la table
+    NoCondition,
+    /// plus de lignes que prévu par expect_at_most, la suppression est annulée
+    TooManyRows {
+        expected: u64,
+        found: u64,
+    },
+    Sqlx(sqlx::Error),
+}
+
+impl fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCondition => write!(f, "No condition: delete would affect the whole table"),
+            Self::TooManyRows { expected, found } => write!(
+                f,
+                "Delete rolled back: {found} rows affected, at most {expected} expected"
+            ),
+            Self::Sqlx(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for DeleteError {}
+
+impl From<sqlx::Error> for DeleteError {
+    fn from(value: sqlx::Error) -> Self {
+        Self::Sqlx(value)
+    }
+}
+
+/// DELETE dont les filtres sont écrits avec les méthodes de SqlOperation,
+/// refusé si aucun filtre n'est écrit
+#[derive(Clone, Debug)]
+pub struct DeleteBuilder {
+    table: Ident,
+    max_rows: Option<u64>,
+}
+
+impl DeleteBuilder {
+    pub fn new(table: Ident) -> Self {
+        Self {
+            table,
+            max_rows: None,
+        }
+    }
+
+    /// La suppression est faite dans une transaction, annulée si plus de max_rows lignes
+    /// sont supprimées
+    pub fn expect_at_most(self, max_rows: u64) -> Self {
+        Self {
+            max_rows: Some(max_rows),
+            ..self
+        }
+    }
+
+    /// "DELETE FROM t WHERE 1=1 AND (1=1" suivi des filtres et de ")": `builder.push_value(" AND id=", id.into())`
+    pub fn build<'a, DB, F>(&self, filters: F) -> Result<QueryBuilder<'a, DB>, DeleteError>
+    where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        let mut builder = QueryBuilder::new("");
+        self.push_delete(&mut builder, filters)?;
+
+        Ok(builder)
+    }
+
+    /// # Returns
+    /// nombre de lignes supprimées
+    pub async fn execute<DB, F>(&self, pool: &Pool<DB>, filters: F) -> Result<u64, DeleteError>
+    where
+        DB: Dialect,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, DB>) + Send,
+    {
+        let mut tx = pool.begin().await?;
+        let result =
+            DB::execute_with(&mut *tx, |builder| self.push_delete(builder, filters)).await?;
+        let count = DB::rows_affected(&result);
+
+        match self.max_rows {
+            Some(max_rows) if count > max_rows => {
+                tx.rollback().await?;
+                Err(DeleteError::TooManyRows {
+                    expected: max_rows,
+                    found: count,
+                })
+            }
+            _ => {
+                tx.commit().await?;
+                Ok(count)
+            }
+        }
+    }
+
+    fn push_delete<'a, DB, F>(
+        &self,
+        builder: &mut QueryBuilder<'a, DB>,
+        filters: F,
+    ) -> Result<(), DeleteError>
+    where
+        DB: Dialect,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        builder.push(format!("DELETE FROM {}", self.table.quoted::<DB>()));
+
+        if push_conditions(builder, filters) {
+            Ok(())
+        } else {
+            Err(DeleteError::NoCondition)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{sqlite::SqlitePoolOptions, MySql, Postgres, Sqlite, SqlitePool};
+
+    use super::*;
+    use crate::types::ValueType;
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE tiers (id INTEGER, code TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO tiers VALUES (1, 'a'), (2, 'b'), (3, 'b')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT count(*) FROM tiers")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn postgres() {
+        let builder = DeleteBuilder::new(Ident::new("tiers").unwrap())
+            .build::<Postgres, _>(|b| b.push_value(" AND id=", 1.into()))
+            .unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            "DELETE FROM \"tiers\" WHERE 1=1 AND (1=1 AND id=$1)"
+        )
+    }
+
+    #[test]
+    fn no_condition() {
+        let result = DeleteBuilder::new(Ident::new("tiers").unwrap())
+            .build::<MySql, _>(|b| b.push_value(" AND id=", ValueType::None));
+
+        assert!(matches!(result, Err(DeleteError::NoCondition)))
+    }
+
+    #[test]
+    fn empty_not_in_is_no_condition() {
+        let result = DeleteBuilder::new(Ident::new("tiers").unwrap())
+            .build::<Sqlite, _>(|b| b.in_int(" AND id NOT IN", &[]));
+
+        assert!(matches!(result, Err(DeleteError::NoCondition)))
+    }
+
+    #[tokio::test]
+    async fn execute() {
+        let pool = pool().await;
+
+        let deleted = DeleteBuilder::new(Ident::new("tiers").unwrap())
+            .expect_at_most(2)
+            .execute(&pool, |b| b.push_value(" AND code=", "b".into()))
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 2);
+        assert_eq!(count(&pool).await, 1);
+    }
+
+    #[tokio::test]
+    async fn or_is_no_condition() {
+        let pool = pool().await;
+        let delete = DeleteBuilder::new(Ident::new("tiers").unwrap()).expect_at_most(100);
+
+        let result = delete
+            .execute(&pool, |b| b.in_int(" OR id NOT IN", &[]))
+            .await;
+        assert!(matches!(result, Err(DeleteError::NoCondition)));
+
+        let result = delete
+            .execute(&pool, |b| b.push_value(" OR code=", "b".into()))
+            .await;
+        assert!(matches!(result, Err(DeleteError::NoCondition)));
+        assert_eq!(count(&pool).await, 3);
+    }
+
+    #[tokio::test]
+    async fn too_many_rows_rolled_back() {
+        let pool = pool().await;
+
+        let result = DeleteBuilder::new(Ident::new("tiers").unwrap())
+            .expect_at_most(1)
+            .execute(&pool, |b| b.push_value(" AND code=", "b".into()))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(DeleteError::TooManyRows {
+                expected: 1,
+                found: 2
+            })
+        ));
+        assert_eq!(count(&pool).await, 3);
+    }
+}
@@ -0,0 +1,53 @@
+use futures::future::BoxFuture;
+use sqlx::{Database, QueryBuilder};
+
+use crate::types::ValueType;
+
+/// Particularités SQL de chaque base supportée (Postgres, MySQL, Sqlite)
+pub trait Dialect: Database {
+    /// nombre maximum de paramètres liés dans une requête
+    const MAX_BIND_PARAMS: usize;
+    /// ORDER BY ... NULLS FIRST / NULLS LAST
+    const SUPPORTS_NULLS_ORDERING: bool;
+    /// caractère entourant les identifiants
+    const IDENT_QUOTE: char;
+    /// INSERT ... RETURNING
+    const SUPPORTS_RETURNING: bool;
+    /// INSERT ... ON CONFLICT, sinon ON DUPLICATE KEY UPDATE
+    const SUPPORTS_ON_CONFLICT: bool;
+    /// comparaison de tuples: (a, b) > (?, ?)
+    const SUPPORTS_ROW_VALUES: bool;
+    /// paramètres numérotés $1, $2 plutôt que ?
+    const NUMBERED_PARAMS: bool;
+
+    /// Id auto-incrémenté généré par un INSERT, si la base le fournit.
+    /// Pour un INSERT multi-lignes: MySQL renvoie celui de la première ligne, Sqlite celui de la dernière
+    fn last_insert_id(result: &Self::QueryResult) -> Option<i64>;
+
+    fn rows_affected(result: &Self::QueryResult) -> u64;
+
+    /// Valeur d'une colonne dont le type n'est connu qu'à l'exécution, NULL donne ValueType::Null
+    fn decode_column(row: &Self::Row, index: usize) -> Result<ValueType, sqlx::Error>;
+
+    /// Littéral SQL équivalent à la valeur, utilisé par debug_sql::render_sql
+    fn literal(value: &ValueType) -> String;
+
+    /// Construit la requête avec build puis l'exécute sur conn, sauf si build renvoie une erreur.
+    /// La requête est construite dans l'implémentation de chaque base car la durée de vie
+    /// des arguments Sqlite empêche d'exécuter un QueryBuilder depuis du code générique
+    fn execute_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Self::QueryResult, E>>
+    where
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c;
+
+    fn fetch_all_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Vec<Self::Row>, E>>
+    where
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c;
+}
@@ -0,0 +1,174 @@
+use sqlx::{
+    types::chrono::{NaiveDate, NaiveDateTime},
+    Column, FromRow, Row,
+};
+
+use crate::{dialect::Dialect, types::ValueType};
+
+/// Ligne lue sans structure dédiée: colonnes dans l'ordre du SELECT, avec leur valeur.
+/// `sqlx::query_as::<_, DynamicRow>("SELECT * FROM tiers")`
+#[derive(Clone, Debug, Default)]
+pub struct DynamicRow {
+    columns: Vec<(String, ValueType)>,
+}
+
+impl DynamicRow {
+    pub fn from_row<R>(row: &R) -> Result<Self, sqlx::Error>
+    where
+        R: Row,
+        R::Database: Dialect<Row = R>,
+    {
+        let columns = row
+            .columns()
+            .iter()
+            .map(|column| {
+                let value = R::Database::decode_column(row, column.ordinal())?;
+                Ok((column.name().to_string(), value))
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(Self { columns })
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ValueType)> {
+        self.columns
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// None si la colonne n'existe pas
+    pub fn get(&self, name: &str) -> Option<&ValueType> {
+        self.columns
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn into_values(self) -> Vec<ValueType> {
+        self.columns.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// None si la colonne est absente, nulle ou d'un autre type
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            ValueType::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Int et BigInt
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            ValueType::Int(x) => Some((*x).into()),
+            ValueType::BigInt(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Float, Double et entiers
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            ValueType::Float(x) => Some((*x).into()),
+            ValueType::Double(x) => Some(*x),
+            ValueType::Int(x) => Some((*x).into()),
+            ValueType::BigInt(x) => Some(*x as f64),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            ValueType::Bool(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn get_date(&self, name: &str) -> Option<NaiveDate> {
+        match self.get(name)? {
+            ValueType::Date(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn get_datetime(&self, name: &str) -> Option<NaiveDateTime> {
+        match self.get(name)? {
+            ValueType::DateTime(x) => Some(*x),
+            ValueType::DateTimeUtc(x) => Some(x.naive_utc()),
+            _ => None,
+        }
+    }
+
+    /// true si la colonne existe et vaut NULL
+    pub fn is_null(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(ValueType::Null))
+    }
+}
+
+impl<'r, R> FromRow<'r, R> for DynamicRow
+where
+    R: Row,
+    R::Database: Dialect<Row = R>,
+{
+    fn from_row(row: &'r R) -> Result<Self, sqlx::Error> {
+        Self::from_row(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sqlite_row() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE tiers (id INTEGER, name TEXT, amount REAL, active BOOLEAN, created DATE, updated DATETIME, city TEXT)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO tiers VALUES (1, 'Hélène', 12.5, 1, '2024-05-01', '2024-05-01 10:30:00', NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let row: DynamicRow = sqlx::query_as("SELECT * FROM tiers")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+        assert_eq!(
+            row.columns().collect::<Vec<_>>(),
+            vec!["id", "name", "amount", "active", "created", "updated", "city"]
+        );
+        assert_eq!(row.get_i64("id"), Some(1));
+        assert_eq!(row.get_str("name"), Some("Hélène"));
+        assert_eq!(row.get_f64("amount"), Some(12.5));
+        assert_eq!(row.get_bool("active"), Some(true));
+        assert_eq!(row.get_date("created"), Some(date));
+        assert_eq!(row.get_datetime("updated"), date.and_hms_opt(10, 30, 0));
+        assert!(row.is_null("city"));
+        assert!(row.get("unknown").is_none());
+    }
+}
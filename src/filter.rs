@@ -0,0 +1,520 @@
+use std::fmt;
+
+use serde::Deserialize;
+use sqlx::{
+    types::chrono::{DateTime, NaiveDate, NaiveDateTime},
+    QueryBuilder,
+};
+
+use crate::{
+    date_parsers::parse_iso_to_date,
+    dialect::Dialect,
+    ident::{Column, ColumnBuf, Ident},
+    operations::SqlOperation,
+    types::ValueType,
+};
+
+/// Type attendu pour la valeur d'un champ filtrable
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterType {
+    String,
+    Int,
+    BigInt,
+    Double,
+    Bool,
+    /// format attendu: yyyy-mm-dd
+    Date,
+    /// format attendu: yyyy-mm-ddTHH:mm:ss, avec ou sans fuseau
+    DateTime,
+}
+
+impl FilterType {
+    /// None si la valeur ne peut pas être convertie
+    fn convert(self, value: ValueType) -> Option<ValueType> {
+        match (self, value) {
+            (_, ValueType::Null) => Some(ValueType::Null),
+            (Self::String, ValueType::String(x)) => Some(ValueType::String(x)),
+            (Self::Int, ValueType::Int(x)) => Some(ValueType::Int(x)),
+            (Self::Int, ValueType::BigInt(x)) => i32::try_from(x).ok().map(ValueType::Int),
+            (Self::BigInt, ValueType::Int(x)) => Some(ValueType::BigInt(x.into())),
+            (Self::BigInt, ValueType::BigInt(x)) => Some(ValueType::BigInt(x)),
+            (Self::Double, ValueType::Int(x)) => Some(ValueType::Double(x.into())),
+            (Self::Double, ValueType::BigInt(x)) => Some(ValueType::Double(x as f64)),
+            (Self::Double, ValueType::Double(x)) => Some(ValueType::Double(x)),
+            (Self::Bool, ValueType::Bool(x)) => Some(ValueType::Bool(x)),
+            (Self::Date, ValueType::String(x)) => parse_iso_to_date(&x).map(ValueType::Date),
+            (Self::DateTime, ValueType::String(x)) => parse_date_time(&x).map(ValueType::DateTime),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FilterType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::BigInt => "bigint",
+            Self::Double => "double",
+            Self::Bool => "bool",
+            Self::Date => "date",
+            Self::DateTime => "datetime",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Heure locale ou convertie en UTC si un fuseau est fourni
+fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(x) = DateTime::parse_from_rfc3339(value) {
+        return Some(x.naive_utc());
+    }
+
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|x| x.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Champ filtrable exposé aux clients
+/// # Arguments
+/// * `name` - nom public, utilisé dans le champ field du filtre
+/// * `column` - expression SQL correspondante, ex: s.sDateCreation, ou Ident quoté selon la base
+/// * `filter_type` - type attendu pour la valeur
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FilterField<'a> {
+    pub name: &'a str,
+    pub column: Column<'a>,
+    pub filter_type: FilterType,
+}
+
+impl<'a> FilterField<'a> {
+    pub const fn new(name: &'a str, column: &'a str, filter_type: FilterType) -> Self {
+        Self {
+            name,
+            column: Column::Raw(column),
+            filter_type,
+        }
+    }
+
+    pub const fn ident(name: &'a str, column: &'a Ident, filter_type: FilterType) -> Self {
+        Self {
+            name,
+            column: Column::Ident(column),
+            filter_type,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    In,
+    NotIn,
+    /// valeur attendue: [début, fin], bornes incluses
+    Between,
+    /// LIKE '%valeur%', champs texte uniquement
+    Contains,
+    /// LIKE 'valeur%', champs texte uniquement
+    StartsWith,
+    /// sans valeur
+    IsNull,
+    /// sans valeur
+    IsNotNull,
+}
+
+impl fmt::Display for FilterOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Eq => "eq",
+            Self::Ne => "ne",
+            Self::Lt => "lt",
+            Self::Lte => "lte",
+            Self::Gt => "gt",
+            Self::Gte => "gte",
+            Self::In => "in",
+            Self::NotIn => "not_in",
+            Self::Between => "between",
+            Self::Contains => "contains",
+            Self::StartsWith => "starts_with",
+            Self::IsNull => "is_null",
+            Self::IsNotNull => "is_not_null",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Nombre maximum de valeurs d'un filtre in / not_in, bien en dessous
+/// de Dialect::MAX_BIND_PARAMS de chaque base, voir Filters::parse_with_max_values
+pub const MAX_LIST_VALUES: usize = 1_000;
+
+/// Filtre envoyé par un client:
+/// `{"field": "date", "op": "between", "value": ["2024-01-01", "2024-01-31"]}`
+#[derive(Clone, Debug, Deserialize)]
+pub struct Filter {
+    pub field: String,
+    pub op: FilterOp,
+    #[serde(default = "absent")]
+    pub value: ValueType,
+}
+
+fn absent() -> ValueType {
+    ValueType::None
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FilterError {
+    UnknownField(String),
+    /// opérateur non applicable au type du champ, ex: contains sur une date
+    InvalidOperator {
+        field: String,
+        op: FilterOp,
+    },
+    /// valeur absente, de mauvais type ou liste de taille incorrecte
+    InvalidValue {
+        field: String,
+        expected: FilterType,
+    },
+    /// liste in / not_in de plus de max valeurs
+    TooManyValues {
+        field: String,
+        max: usize,
+    },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(name) => write!(f, "Unknown filter field: {name}"),
+            Self::InvalidOperator { field, op } => {
+                write!(f, "Operator {op} is not allowed on field {field}")
+            }
+            Self::InvalidValue { field, expected } => {
+                write!(f, "Invalid value for field {field}, expected {expected}")
+            }
+            Self::TooManyValues { field, max } => {
+                write!(
+                    f,
+                    "Too many values for field {field}, at most {max} allowed"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// Filtre validé, prêt à être écrit dans la requête
+enum Condition {
+    Compare(&'static str, ValueType),
+    In(bool, Vec<ValueType>),
+    Between(ValueType, ValueType),
+    Contains(ValueType),
+    StartsWith(ValueType),
+    IsNull(bool),
+}
+
+struct CompiledFilter {
+    column: ColumnBuf,
+    condition: Condition,
+}
+
+/// Filtres validés contre la liste des champs autorisés
+#[derive(Default)]
+pub struct Filters {
+    filters: Vec<CompiledFilter>,
+}
+
+impl Filters {
+    /// Les listes in / not_in sont limitées à MAX_LIST_VALUES valeurs
+    /// # Arguments
+    /// * `filters` - filtres reçus du client
+    /// * `fields` - liste des champs autorisés
+    pub fn parse(filters: Vec<Filter>, fields: &[FilterField]) -> Result<Self, FilterError> {
+        Self::parse_with_max_values(filters, fields, MAX_LIST_VALUES)
+    }
+
+    /// parse avec une autre limite pour les listes in / not_in,
+    /// qui doit rester inférieure à default_chunk_size::<DB>()
+    pub fn parse_with_max_values(
+        filters: Vec<Filter>,
+        fields: &[FilterField],
+        max_values: usize,
+    ) -> Result<Self, FilterError> {
+        let filters = filters
+            .into_iter()
+            .map(|filter| compile(filter, fields, max_values))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { filters })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Ecrit chaque filtre sous la forme " AND condition"
+    pub fn push<'a, DB>(self, builder: &mut QueryBuilder<'a, DB>)
+    where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+    {
+        for CompiledFilter { column, condition } in self.filters {
+            let column = column.as_column();
+            match condition {
+                Condition::Compare(operator, value) => {
+                    builder.push_value(column.around(" AND ", operator), value);
+                }
+                Condition::In(negated, values) => {
+                    let operator = if negated { " NOT IN" } else { " IN" };
+                    builder.in_value_types(column.around(" AND ", operator), &values);
+                }
+                Condition::Between(start, end) => {
+                    builder.push(format!(" AND {} BETWEEN ", column.to_sql::<DB>()));
+                    builder.bind(start);
+                    builder.push(" AND ");
+                    builder.bind(end);
+                }
+                Condition::Contains(value) => {
+                    builder.like_within(column.around(" AND ", ""), value);
+                }
+                Condition::StartsWith(value) => {
+                    builder.like_starts_with(column.around(" AND ", ""), value);
+                }
+                Condition::IsNull(true) => builder.and_is_null(column),
+                Condition::IsNull(false) => builder.and_is_not_null(column),
+            }
+        }
+    }
+}
+
+fn compile(
+    filter: Filter,
+    fields: &[FilterField],
+    max_values: usize,
+) -> Result<CompiledFilter, FilterError> {
+    let field = fields
+        .iter()
+        .find(|x| x.name == filter.field)
+        .ok_or_else(|| FilterError::UnknownField(filter.field.clone()))?;
+    let invalid_value = || FilterError::InvalidValue {
+        field: filter.field.clone(),
+        expected: field.filter_type,
+    };
+    let convert = |value: ValueType| match field.filter_type.convert(value) {
+        Some(ValueType::Null) | None => Err(invalid_value()),
+        Some(x) => Ok(x),
+    };
+    let list = |value: ValueType| match value {
+        ValueType::List(values) if values.len() > max_values => Err(FilterError::TooManyValues {
+            field: filter.field.clone(),
+            max: max_values,
+        }),
+        ValueType::List(values) => values.into_iter().map(convert).collect(),
+        _ => Err(invalid_value()),
+    };
+
+    let condition = match filter.op {
+        FilterOp::Eq | FilterOp::Ne => {
+            // null accepté: IS NULL / IS NOT NULL
+            let value = match field.filter_type.convert(filter.value) {
+                Some(x) => x,
+                None => return Err(invalid_value()),
+            };
+            let operator = if filter.op == FilterOp::Eq { "=" } else { "<>" };
+            Condition::Compare(operator, value)
+        }
+        FilterOp::Lt => Condition::Compare("<", convert(filter.value)?),
+        FilterOp::Lte => Condition::Compare("<=", convert(filter.value)?),
+        FilterOp::Gt => Condition::Compare(">", convert(filter.value)?),
+        FilterOp::Gte => Condition::Compare(">=", convert(filter.value)?),
+        FilterOp::In => Condition::In(false, list(filter.value)?),
+        FilterOp::NotIn => Condition::In(true, list(filter.value)?),
+        FilterOp::Between => {
+            let mut values = list(filter.value)?;
+            if values.len() != 2 {
+                return Err(invalid_value());
+            }
+            let end = values.pop().unwrap_or(ValueType::None);
+            let start = values.pop().unwrap_or(ValueType::None);
+            Condition::Between(start, end)
+        }
+        FilterOp::Contains | FilterOp::StartsWith => {
+            if field.filter_type != FilterType::String {
+                return Err(FilterError::InvalidOperator {
+                    field: filter.field,
+                    op: filter.op,
+                });
+            }
+            let value = convert(filter.value)?;
+            if filter.op == FilterOp::Contains {
+                Condition::Contains(value)
+            } else {
+                Condition::StartsWith(value)
+            }
+        }
+        FilterOp::IsNull => Condition::IsNull(true),
+        FilterOp::IsNotNull => Condition::IsNull(false),
+    };
+
+    Ok(CompiledFilter {
+        column: field.column.into(),
+        condition,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{MySql, Postgres, Sqlite};
+
+    use super::*;
+
+    const FIELDS: [FilterField; 4] = [
+        FilterField::new("date", "s.sDateCreation", FilterType::Date),
+        FilterField::new("name", "s.sNom", FilterType::String),
+        FilterField::new("count", "s.nCount", FilterType::Int),
+        FilterField::new("code", "s.sCode", FilterType::String),
+    ];
+
+    fn parse(json: &str) -> Result<Filters, FilterError> {
+        Filters::parse(serde_json::from_str(json).unwrap(), &FIELDS)
+    }
+
+    #[test]
+    fn postgres_filters() {
+        let filters = parse(
+            r#"[
+                {"field": "date", "op": "between", "value": ["2024-01-01", "2024-01-31"]},
+                {"field": "count", "op": "gte", "value": 3},
+                {"field": "code", "op": "in", "value": ["A", "B"]},
+                {"field": "name", "op": "eq", "value": null}
+            ]"#,
+        )
+        .unwrap();
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT * FROM s WHERE 1=1");
+        filters.push(&mut builder);
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM s WHERE 1=1 AND s.sDateCreation BETWEEN $1 AND $2 AND s.nCount>=$3 AND s.sCode IN ($4,$5) AND s.sNom IS NULL"
+        );
+    }
+
+    #[test]
+    fn mysql_filters() {
+        let filters = parse(
+            r#"[
+                {"field": "name", "op": "contains", "value": "dup"},
+                {"field": "code", "op": "not_in", "value": ["A", "B"]},
+                {"field": "date", "op": "is_not_null"}
+            ]"#,
+        )
+        .unwrap();
+        let mut builder = QueryBuilder::<MySql>::new("SELECT * FROM s WHERE 1=1");
+        filters.push(&mut builder);
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM s WHERE 1=1 AND s.sNom LIKE CONCAT('%',REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!' AND s.sCode NOT IN (?,?) AND s.sDateCreation IS NOT NULL"
+        );
+    }
+
+    #[test]
+    fn invalid_filters() {
+        assert_eq!(
+            parse(r#"[{"field": "password", "op": "eq", "value": "x"}]"#).err(),
+            Some(FilterError::UnknownField("password".to_string()))
+        );
+        assert_eq!(
+            parse(r#"[{"field": "date", "op": "eq", "value": "01/01/2024"}]"#).err(),
+            Some(FilterError::InvalidValue {
+                field: "date".to_string(),
+                expected: FilterType::Date
+            })
+        );
+        assert_eq!(
+            parse(r#"[{"field": "date", "op": "between", "value": ["2024-01-01"]}]"#).err(),
+            Some(FilterError::InvalidValue {
+                field: "date".to_string(),
+                expected: FilterType::Date
+            })
+        );
+        assert_eq!(
+            parse(r#"[{"field": "count", "op": "contains", "value": 1}]"#).err(),
+            Some(FilterError::InvalidOperator {
+                field: "count".to_string(),
+                op: FilterOp::Contains
+            })
+        );
+        assert!(parse(r#"[{"field": "count", "op": "lt"}]"#).is_err());
+    }
+
+    #[test]
+    fn too_many_values() {
+        let values = (0..40_000).collect::<Vec<_>>();
+        let json = format!(r#"[{{"field": "count", "op": "in", "value": {values:?}}}]"#);
+
+        assert_eq!(
+            parse(&json).err(),
+            Some(FilterError::TooManyValues {
+                field: "count".to_string(),
+                max: MAX_LIST_VALUES
+            })
+        );
+
+        let filters: Vec<Filter> =
+            serde_json::from_str(r#"[{"field": "count", "op": "not_in", "value": [1, 2, 3]}]"#)
+                .unwrap();
+        assert_eq!(
+            Filters::parse_with_max_values(filters, &FIELDS, 2).err(),
+            Some(FilterError::TooManyValues {
+                field: "count".to_string(),
+                max: 2
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_filters() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE s (sDateCreation DATE, sNom TEXT, nCount INTEGER, sCode TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO s VALUES ('2024-01-10', 'Dupont', 4, 'A'), ('2024-02-10', 'Durand', 1, 'B')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let filters = parse(
+            r#"[
+                {"field": "date", "op": "between", "value": ["2024-01-01", "2024-01-31"]},
+                {"field": "name", "op": "starts_with", "value": "Du"}
+            ]"#,
+        )
+        .unwrap();
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT sNom FROM s WHERE 1=1");
+        filters.push(&mut builder);
+        let names: Vec<(String,)> = builder.build_query_as().fetch_all(&pool).await.unwrap();
+
+        assert_eq!(names, vec![("Dupont".to_string(),)]);
+    }
+}
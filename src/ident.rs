@@ -0,0 +1,347 @@
+use std::{borrow::Cow, fmt};
+
+use crate::dialect::Dialect;
+
+/// Longueur maximale commune (Postgres: 63, MySQL: 64)
+pub const MAX_IDENT_LEN: usize = 63;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IdentError {
+    Empty,
+    TooLong(String),
+    InvalidCharacter(String),
+    TooManyParts(String),
+}
+
+impl fmt::Display for IdentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Empty identifier"),
+            Self::TooLong(x) => write!(f, "Identifier too long: {x}"),
+            Self::InvalidCharacter(x) => write!(f, "Invalid character in identifier: {x}"),
+            Self::TooManyParts(x) => write!(f, "Too many parts in identifier: {x}"),
+        }
+    }
+}
+
+impl std::error::Error for IdentError {}
+
+/// Nom de colonne, table ou schéma validé, éventuellement qualifié (schema.table.column).
+/// Seuls lettres ASCII, chiffres, _ et $ sont acceptés, le premier caractère n'étant pas un chiffre.
+/// Les mots réservés (order, user...) sont supportés grâce aux guillemets
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ident {
+    parts: Vec<String>,
+}
+
+impl Ident {
+    /// Identifiant simple, sans point
+    pub fn new(name: &str) -> Result<Self, IdentError> {
+        validate_part(name)?;
+
+        Ok(Self {
+            parts: vec![name.to_string()],
+        })
+    }
+
+    /// format attendu: column, table.column ou schema.table.column
+    pub fn parse(value: &str) -> Result<Self, IdentError> {
+        let parts = value.split('.').collect::<Vec<_>>();
+
+        if parts.len() > 3 {
+            return Err(IdentError::TooManyParts(value.to_string()));
+        }
+
+        Self::qualified(&parts)
+    }
+
+    pub fn qualified(parts: &[&str]) -> Result<Self, IdentError> {
+        if parts.is_empty() {
+            return Err(IdentError::Empty);
+        }
+
+        for part in parts {
+            validate_part(part)?;
+        }
+
+        Ok(Self {
+            parts: parts.iter().map(|x| x.to_string()).collect(),
+        })
+    }
+
+    /// Dernière partie: la colonne pour schema.table.column
+    pub fn name(&self) -> &str {
+        self.parts.last().map(String::as_str).unwrap_or_default()
+    }
+
+    /// `"schema"."table"` pour Postgres et Sqlite, `` `schema`.`table` `` pour MySQL
+    pub fn quoted<DB: Dialect>(&self) -> String {
+        self.parts
+            .iter()
+            .map(|x| quote_part(x, DB::IDENT_QUOTE))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Fragment sql avec l'identifiant quoté selon la base entre before et after:
+    /// `code.around(" AND ", "=")` donne ` AND "code"=` sur Postgres
+    pub fn around<'s>(&'s self, before: &'s str, after: &'s str) -> SqlFragment<'s> {
+        SqlFragment {
+            before,
+            column: Some(Column::Ident(self)),
+            after,
+        }
+    }
+}
+
+impl TryFrom<&str> for Ident {
+    type Error = IdentError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.parts.join("."))
+    }
+}
+
+/// Colonne acceptée par SqlOperation, SortField et FilterField:
+/// expression sql écrite telle quelle (s.sDateCreation) ou Ident quoté selon la base
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column<'s> {
+    Raw(&'s str),
+    Ident(&'s Ident),
+}
+
+impl<'s> Column<'s> {
+    pub fn to_sql<DB: Dialect>(self) -> Cow<'s, str> {
+        match self {
+            Self::Raw(x) => Cow::Borrowed(x),
+            Self::Ident(x) => Cow::Owned(x.quoted::<DB>()),
+        }
+    }
+
+    /// Fragment sql avec la colonne entre before et after, voir Ident::around
+    pub fn around(self, before: &'s str, after: &'s str) -> SqlFragment<'s> {
+        SqlFragment {
+            before,
+            column: Some(self),
+            after,
+        }
+    }
+}
+
+impl<'s> From<&'s str> for Column<'s> {
+    fn from(value: &'s str) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl<'s> From<&'s String> for Column<'s> {
+    fn from(value: &'s String) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl<'s> From<&'s Ident> for Column<'s> {
+    fn from(value: &'s Ident) -> Self {
+        Self::Ident(value)
+    }
+}
+
+impl<'s> From<&'s ColumnBuf> for Column<'s> {
+    fn from(value: &'s ColumnBuf) -> Self {
+        match value {
+            ColumnBuf::Raw(x) => Self::Raw(x),
+            ColumnBuf::Ident(x) => Self::Ident(x),
+        }
+    }
+}
+
+/// Sans quotes, pour les messages et les logs
+impl fmt::Display for Column<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Raw(x) => write!(f, "{x}"),
+            Self::Ident(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+/// Column possédée, conservée par SortKey et les filtres compilés
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnBuf {
+    Raw(String),
+    Ident(Ident),
+}
+
+impl ColumnBuf {
+    pub fn as_column(&self) -> Column<'_> {
+        self.into()
+    }
+}
+
+impl From<Column<'_>> for ColumnBuf {
+    fn from(value: Column<'_>) -> Self {
+        match value {
+            Column::Raw(x) => Self::Raw(x.to_string()),
+            Column::Ident(x) => Self::Ident(x.clone()),
+        }
+    }
+}
+
+impl From<String> for ColumnBuf {
+    fn from(value: String) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl From<Ident> for ColumnBuf {
+    fn from(value: Ident) -> Self {
+        Self::Ident(value)
+    }
+}
+
+impl fmt::Display for ColumnBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_column())
+    }
+}
+
+/// Fragment sql passé aux méthodes de SqlOperation: texte brut (" AND code=")
+/// ou colonne entre un préfixe et un suffixe, voir Ident::around
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SqlFragment<'s> {
+    before: &'s str,
+    column: Option<Column<'s>>,
+    after: &'s str,
+}
+
+impl<'s> SqlFragment<'s> {
+    pub fn to_sql<DB: Dialect>(&self) -> Cow<'s, str> {
+        match &self.column {
+            None => Cow::Borrowed(self.before),
+            Some(column) => Cow::Owned(format!(
+                "{}{}{}",
+                self.before,
+                column.to_sql::<DB>(),
+                self.after
+            )),
+        }
+    }
+}
+
+impl<'s> From<&'s str> for SqlFragment<'s> {
+    fn from(value: &'s str) -> Self {
+        Self {
+            before: value,
+            column: None,
+            after: "",
+        }
+    }
+}
+
+impl<'s> From<&'s String> for SqlFragment<'s> {
+    fn from(value: &'s String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+fn validate_part(part: &str) -> Result<(), IdentError> {
+    let mut chars = part.chars();
+
+    match chars.next() {
+        None => Err(IdentError::Empty),
+        _ if part.len() > MAX_IDENT_LEN => Err(IdentError::TooLong(part.to_string())),
+        Some(first) if first.is_ascii_digit() => {
+            Err(IdentError::InvalidCharacter(part.to_string()))
+        }
+        _ if !part
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || x == '_' || x == '$') =>
+        {
+            Err(IdentError::InvalidCharacter(part.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Les guillemets sont doublés par sécurité, bien que refusés par la validation
+fn quote_part(part: &str, quote: char) -> String {
+    let escaped = part.replace(quote, &format!("{quote}{quote}"));
+
+    format!("{quote}{escaped}{quote}")
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{MySql, Postgres, Sqlite};
+
+    use super::*;
+
+    #[test]
+    fn quote_per_backend() {
+        let ident = Ident::new("order").unwrap();
+
+        assert_eq!(ident.quoted::<Postgres>(), "\"order\"");
+        assert_eq!(ident.quoted::<Sqlite>(), "\"order\"");
+        assert_eq!(ident.quoted::<MySql>(), "`order`");
+    }
+
+    #[test]
+    fn qualified() {
+        let ident = Ident::parse("tw.tiers.sCode").unwrap();
+
+        assert_eq!(ident.quoted::<MySql>(), "`tw`.`tiers`.`sCode`");
+        assert_eq!(ident.name(), "sCode");
+        assert_eq!(ident.to_string(), "tw.tiers.sCode");
+    }
+
+    #[test]
+    fn fragments() {
+        let ident = Ident::new("order").unwrap();
+
+        assert_eq!(
+            SqlFragment::from(" AND code=").to_sql::<MySql>(),
+            " AND code="
+        );
+        assert_eq!(
+            ident.around(" AND ", "<>").to_sql::<Postgres>(),
+            " AND \"order\"<>"
+        );
+        assert_eq!(
+            Column::from("s.sCode")
+                .around(" AND ", " IN")
+                .to_sql::<Sqlite>(),
+            " AND s.sCode IN"
+        );
+        assert_eq!(ColumnBuf::from(ident).as_column().to_string(), "order");
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Ident::parse(""), Err(IdentError::Empty));
+        assert_eq!(Ident::parse("a..b"), Err(IdentError::Empty));
+        assert_eq!(
+            Ident::parse("a.b.c.d"),
+            Err(IdentError::TooManyParts("a.b.c.d".to_string()))
+        );
+        assert_eq!(
+            Ident::new("code\"; DROP TABLE tiers; --"),
+            Err(IdentError::InvalidCharacter(
+                "code\"; DROP TABLE tiers; --".to_string()
+            ))
+        );
+        assert_eq!(
+            Ident::new("1code"),
+            Err(IdentError::InvalidCharacter("1code".to_string()))
+        );
+        assert!(matches!(
+            Ident::new(&"a".repeat(64)),
+            Err(IdentError::TooLong(_))
+        ));
+    }
+}
@@ -0,0 +1,438 @@
+use std::{fmt, future::Future};
+
+use sqlx::{Database, Pool};
+
+use crate::{
+    dialect::Dialect,
+    operations::{split_connector, SqlOperation, SqlSink},
+    types::ValueType,
+};
+
+/// Manière de lier les valeurs d'une clause IN
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InBinding {
+    /// un paramètre par valeur: `code IN ($1,$2,...)`
+    #[default]
+    Expanded,
+    /// un seul paramètre tableau: `code = ANY($1)` ou `code <> ALL($1)`.
+    /// Postgres uniquement, MySQL et Sqlite restent en Expanded
+    Array,
+}
+
+/// Comportement lorsque la liste de valeurs est vide
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyInList {
+    /// aucun filtre n'est écrit: toutes les lignes sont retournées
+    Skip,
+    /// `AND 1=0` pour IN. NOT IN ne filtre rien: aucune condition n'est écrite,
+    /// quel que soit le connecteur (`OR 1=1` étendrait la requête à toutes les lignes)
+    #[default]
+    MatchNothing,
+    /// renvoie InListError::Empty
+    Error,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InListError {
+    Empty(String),
+    /// la liste dépasse InOptions::max_values, voir fetch_all_in_chunks
+    TooManyValues {
+        count: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for InListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty(sql) => write!(f, "Empty value list for: {sql}"),
+            Self::TooManyValues { count, max } => write!(
+                f,
+                "{count} values exceed the maximum of {max} values in one statement, use fetch_all_in_chunks"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InListError {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InOptions {
+    pub binding: InBinding,
+    pub on_empty: EmptyInList,
+    /// au-delà de chunk_size valeurs, la liste est découpée en `(code IN (...) OR code IN (...))`,
+    /// pour les bases qui limitent la taille d'une liste IN. Les paquets restent dans la même
+    /// requête: le nombre de paramètres liés ne change pas. Par défaut la liste n'est pas découpée
+    pub chunk_size: Option<usize>,
+    /// au-delà de max_values valeurs, rien n'est écrit et InListError::TooManyValues est renvoyé.
+    /// Par défaut default_chunk_size::<DB>(), qui laisse RESERVED_BIND_PARAMS aux autres paramètres
+    pub max_values: Option<usize>,
+}
+
+impl InOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_binding(self, binding: InBinding) -> Self {
+        Self { binding, ..self }
+    }
+
+    pub fn with_on_empty(self, on_empty: EmptyInList) -> Self {
+        Self { on_empty, ..self }
+    }
+
+    pub fn with_chunk_size(self, chunk_size: usize) -> Self {
+        Self {
+            chunk_size: Some(chunk_size.max(1)),
+            ..self
+        }
+    }
+
+    pub fn with_max_values(self, max_values: usize) -> Self {
+        Self {
+            max_values: Some(max_values),
+            ..self
+        }
+    }
+
+    /// Options des méthodes in_xxx sans options: la liste est écrite quelle que soit sa taille,
+    /// la base renvoie l'erreur à l'exécution si elle dépasse son nombre de paramètres
+    pub(crate) fn unbounded() -> Self {
+        Self::new().with_max_values(usize::MAX)
+    }
+}
+
+/// Paramètres laissés disponibles pour les autres filtres et la pagination
+/// lorsque la taille des paquets est déduite de Dialect::MAX_BIND_PARAMS
+pub const RESERVED_BIND_PARAMS: usize = 100;
+
+/// Taille des paquets de fetch_all_in_chunks, et nombre maximum de valeurs accepté
+/// par les méthodes in_xxx_with
+pub fn default_chunk_size<DB: Dialect>() -> usize {
+    DB::MAX_BIND_PARAMS - RESERVED_BIND_PARAMS
+}
+
+/// "AND code NOT IN" décomposé en connecteur ("AND "), colonne ("code") et négation
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct InClause<'s> {
+    pub connector: &'s str,
+    pub column: &'s str,
+    pub negated: bool,
+}
+
+impl<'s> InClause<'s> {
+    pub fn parse(sql: &'s str) -> Option<Self> {
+        let (left, negated) = split_in_operator(sql)?;
+        let (connector, column) = split_connector(left);
+
+        Some(Self {
+            connector,
+            column,
+            negated,
+        })
+    }
+
+    pub fn operator(&self) -> &'static str {
+        if self.negated {
+            "NOT IN"
+        } else {
+            "IN"
+        }
+    }
+}
+
+/// Sépare "AND code NOT IN" en ("AND code", true)
+/// # Returns
+/// None si sql ne se termine pas par IN ou NOT IN
+pub(crate) fn split_in_operator(sql: &str) -> Option<(&str, bool)> {
+    let trimmed = sql.trim_end();
+    let upper = trimmed.to_ascii_uppercase();
+
+    if upper.ends_with(" NOT IN") {
+        Some((&trimmed[..trimmed.len() - 7], true))
+    } else if upper.ends_with(" IN") {
+        Some((&trimmed[..trimmed.len() - 3], false))
+    } else {
+        None
+    }
+}
+
+/// Ecrit la clause IN avec un paramètre par valeur (les ValueType::None sont ignorés).
+/// Au-delà de options.chunk_size valeurs, la liste devient `AND (code IN (...) OR code IN (...))`,
+/// ou `AND (code NOT IN (...) AND code NOT IN (...))`, dans la même requête.
+/// Au-delà de options.max_values valeurs, rien n'est écrit et InListError::TooManyValues
+/// est renvoyé, voir fetch_all_in_chunks. Une liste vide est traitée selon options.on_empty
+pub(crate) fn push_in_list<S: SqlSink>(
+    builder: &mut S,
+    sql: &str,
+    values: &[ValueType],
+    options: InOptions,
+) -> Result<(), InListError> {
+    let values = values
+        .iter()
+        .filter(|x| !matches!(x, ValueType::None))
+        .collect::<Vec<_>>();
+
+    if values.is_empty() {
+        return push_empty(builder, sql, options.on_empty);
+    }
+    let max = options
+        .max_values
+        .unwrap_or_else(default_chunk_size::<S::Database>);
+    if values.len() > max {
+        return Err(InListError::TooManyValues {
+            count: values.len(),
+            max,
+        });
+    }
+
+    match (InClause::parse(sql), options.chunk_size) {
+        (Some(clause), Some(size)) if values.len() > size => {
+            let separator = if clause.negated { " AND " } else { " OR " };
+
+            builder.push(format!("{}(", clause.connector));
+            for (i, chunk) in values.chunks(size).enumerate() {
+                if i > 0 {
+                    builder.push(separator);
+                }
+                builder.push(format!("{} {} ", clause.column, clause.operator()));
+                push_list(builder, chunk);
+            }
+            builder.push(")");
+        }
+        _ => {
+            builder.push(format!("{sql} "));
+            push_list(builder, &values);
+        }
+    }
+
+    Ok(())
+}
+
+/// Sans clause IN ou NOT IN reconnue, MatchNothing ne peut pas choisir entre 1=0 et 1=1:
+/// InListError::Empty est renvoyé et rien n'est écrit
+fn push_empty<S: SqlSink>(
+    builder: &mut S,
+    sql: &str,
+    on_empty: EmptyInList,
+) -> Result<(), InListError> {
+    match (on_empty, InClause::parse(sql)) {
+        (EmptyInList::Skip, _) => {}
+        (EmptyInList::Error, _) | (EmptyInList::MatchNothing, None) => {
+            return Err(InListError::Empty(sql.to_string()))
+        }
+        // rien n'est écrit, pour que push_conditions ne le prenne pas pour une condition
+        (EmptyInList::MatchNothing, Some(clause)) if clause.negated => {}
+        (EmptyInList::MatchNothing, Some(clause)) => {
+            builder.push(format!("{}1=0", clause.connector));
+        }
+    };
+
+    Ok(())
+}
+
+/// "(?,?,?)", utilisé aussi pour lier un ValueType::List sur MySQL et Sqlite
+pub(crate) fn push_list<S: SqlSink>(builder: &mut S, values: &[&ValueType]) {
+    builder.push("(");
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            builder.push(",");
+        }
+        SqlOperation::bind(builder, (*value).clone());
+    }
+    builder.push(")");
+}
+
+/// Exécute une requête par paquet de valeurs et concatène les résultats.
+/// A utiliser lorsque la liste dépasse le nombre de paramètres autorisés par la base.
+/// # Arguments
+/// * `pool`
+/// * `values` - liste complète, découpée en paquets de chunk_size
+/// * `chunk_size` - par défaut default_chunk_size::<DB>()
+/// * `fetch` - construit et exécute la requête pour un paquet de valeurs
+pub async fn fetch_all_in_chunks<DB, T, V, F, Fut>(
+    pool: &Pool<DB>,
+    values: &[V],
+    chunk_size: Option<usize>,
+    mut fetch: F,
+) -> Result<Vec<T>, sqlx::Error>
+where
+    DB: Database + Dialect,
+    V: Clone,
+    F: FnMut(Pool<DB>, Vec<V>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, sqlx::Error>>,
+{
+    let size = chunk_size.unwrap_or_else(default_chunk_size::<DB>).max(1);
+    let mut items = vec![];
+
+    for chunk in values.chunks(size) {
+        let mut rows = fetch(pool.clone(), chunk.to_vec()).await?;
+        items.append(&mut rows);
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{sqlite::SqlitePoolOptions, MySql, QueryBuilder, Sqlite};
+
+    use super::*;
+
+    #[test]
+    fn parse_clause() {
+        assert_eq!(
+            InClause::parse(" and t.code NOT IN"),
+            Some(InClause {
+                connector: " and ",
+                column: "t.code",
+                negated: true
+            })
+        );
+        assert_eq!(
+            InClause::parse("code IN"),
+            Some(InClause {
+                connector: "",
+                column: "code",
+                negated: false
+            })
+        );
+    }
+
+    #[test]
+    fn empty_skip() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let options = InOptions::new().with_on_empty(EmptyInList::Skip);
+        let result = push_in_list(&mut builder, "AND code IN", &[], options);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(builder.sql(), "")
+    }
+
+    #[test]
+    fn empty_error() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let options = InOptions::new().with_on_empty(EmptyInList::Error);
+        let result = push_in_list(&mut builder, "AND code IN", &[ValueType::None], options);
+
+        assert_eq!(result, Err(InListError::Empty("AND code IN".to_string())));
+        assert_eq!(builder.sql(), "")
+    }
+
+    #[test]
+    fn empty_not_in() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        push_in_list(&mut builder, " AND code NOT IN", &[], InOptions::new()).unwrap();
+        push_in_list(&mut builder, " OR code NOT IN", &[], InOptions::new()).unwrap();
+
+        assert_eq!(builder.sql(), "")
+    }
+
+    #[test]
+    fn empty_unknown_clause_skipped() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        builder.in_str::<&str>(" AND code = ANY", &[]);
+
+        assert_eq!(builder.sql(), "")
+    }
+
+    #[test]
+    fn empty_unknown_clause() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let result = push_in_list(&mut builder, " AND code = ANY", &[], InOptions::new());
+
+        assert_eq!(
+            result,
+            Err(InListError::Empty(" AND code = ANY".to_string()))
+        );
+        assert_eq!(builder.sql(), "")
+    }
+
+    #[test]
+    fn not_chunked_by_default() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let values = (0..2_000).map(ValueType::Int).collect::<Vec<_>>();
+        push_in_list(&mut builder, " AND id IN", &values, InOptions::new()).unwrap();
+
+        assert!(builder.sql().starts_with(" AND id IN (?,?"));
+        assert!(!builder.sql().contains(" OR "));
+    }
+
+    #[test]
+    fn too_many_values() {
+        assert_eq!(default_chunk_size::<Sqlite>(), 32_666);
+
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let values = (0..32_667).map(ValueType::Int).collect::<Vec<_>>();
+        let options = InOptions::new().with_chunk_size(1_000);
+        let result = push_in_list(&mut builder, " AND id IN", &values, options);
+
+        assert_eq!(
+            result,
+            Err(InListError::TooManyValues {
+                count: 32_667,
+                max: 32_666
+            })
+        );
+        assert_eq!(builder.sql(), "");
+
+        let options = InOptions::new().with_max_values(10);
+        let result = push_in_list(&mut builder, " AND id IN", &values[..11], options);
+        assert_eq!(
+            result,
+            Err(InListError::TooManyValues { count: 11, max: 10 })
+        );
+    }
+
+    #[test]
+    fn unbounded_does_not_fail() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let values = (0..70_000).collect::<Vec<i32>>();
+        builder.in_int(" AND id IN", &values);
+
+        assert_eq!(builder.sql().matches('?').count(), 70_000);
+    }
+
+    #[tokio::test]
+    async fn fetch_in_chunks() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let ids = (1..=10).collect::<Vec<i32>>();
+
+        let rows: Vec<(i32,)> =
+            fetch_all_in_chunks(&pool, &ids, Some(3), |pool, chunk| async move {
+                let mut builder: QueryBuilder<'_, Sqlite> =
+                    QueryBuilder::new("SELECT value FROM json_each(");
+                builder.push_bind(format!("{chunk:?}"));
+                builder.push(")");
+                builder.build_query_as().fetch_all(&pool).await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(rows.into_iter().map(|x| x.0).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn split_in() {
+        assert_eq!(split_in_operator("AND code IN"), Some(("AND code", false)));
+        assert_eq!(split_in_operator("AND code in "), Some(("AND code", false)));
+    }
+
+    #[test]
+    fn split_not_in() {
+        assert_eq!(
+            split_in_operator("AND code NOT IN"),
+            Some(("AND code", true))
+        );
+    }
+
+    #[test]
+    fn split_without_in() {
+        assert_eq!(split_in_operator("AND code ="), None);
+    }
+}
@@ -0,0 +1,425 @@
+use std::fmt;
+
+use sqlx::{ColumnIndex, Decode, FromRow, Pool, QueryBuilder, Row, Type};
+
+use crate::{
+    dialect::Dialect, ident::Ident, operations::SqlOperation, types::ValueType, upsert::OnConflict,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InsertError {
+    ColumnCount { expected: usize, found: usize },
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ColumnCount { expected, found } => {
+                write!(f, "Expected {expected} values per row, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+/// INSERT multi-lignes découpé en lots respectant Dialect::MAX_BIND_PARAMS.
+/// Dans une ligne, ValueType::None et ValueType::Null insèrent NULL
+#[derive(Clone, Debug)]
+pub struct InsertBuilder {
+    table: Ident,
+    columns: Vec<Ident>,
+    rows: Vec<Vec<ValueType>>,
+    returning: Vec<Ident>,
+    on_conflict: Option<OnConflict>,
+    batch_size: Option<usize>,
+}
+
+impl InsertBuilder {
+    pub fn new(table: Ident, columns: Vec<Ident>) -> Self {
+        Self {
+            table,
+            columns,
+            rows: vec![],
+            returning: vec![],
+            on_conflict: None,
+            batch_size: None,
+        }
+    }
+
+    /// Colonnes renvoyées par RETURNING (Postgres et Sqlite)
+    pub fn with_returning(self, returning: Vec<Ident>) -> Self {
+        Self { returning, ..self }
+    }
+
+    /// Upsert: ON CONFLICT sur Postgres et Sqlite, ON DUPLICATE KEY UPDATE sur MySQL.
+    /// Sur MySQL, insert_ids n'est alors plus fiable (lignes ignorées ou mises à jour)
+    pub fn with_on_conflict(self, on_conflict: OnConflict) -> Self {
+        Self {
+            on_conflict: Some(on_conflict),
+            ..self
+        }
+    }
+
+    /// Nombre de lignes par requête, par défaut le maximum permis par la base
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: Some(batch_size.max(1)),
+            ..self
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<ValueType>) -> Result<(), InsertError> {
+        if row.len() != self.columns.len() {
+            return Err(InsertError::ColumnCount {
+                expected: self.columns.len(),
+                found: row.len(),
+            });
+        }
+
+        self.rows.push(row);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn rows_per_batch<DB: Dialect>(&self) -> usize {
+        let max = (DB::MAX_BIND_PARAMS / self.columns.len().max(1)).max(1);
+
+        self.batch_size.map(|x| x.min(max)).unwrap_or(max)
+    }
+
+    /// Une requête par lot, avec RETURNING si demandé et supporté par la base
+    pub fn build<'a, DB>(&self) -> Vec<QueryBuilder<'a, DB>>
+    where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+    {
+        let returning = self.returning_columns::<DB>();
+
+        self.rows
+            .chunks(self.rows_per_batch::<DB>())
+            .map(|rows| {
+                let mut builder = QueryBuilder::new("");
+                self.push_batch(&mut builder, rows, &returning);
+                builder
+            })
+            .collect()
+    }
+
+    fn returning_columns<DB: Dialect>(&self) -> Vec<String> {
+        if DB::SUPPORTS_RETURNING {
+            self.returning.iter().map(|x| x.quoted::<DB>()).collect()
+        } else {
+            vec![]
+        }
+    }
+
+    fn push_batch<'a, DB>(
+        &self,
+        builder: &mut QueryBuilder<'a, DB>,
+        rows: &[Vec<ValueType>],
+        returning: &[String],
+    ) where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+    {
+        let columns = self
+            .columns
+            .iter()
+            .map(|x| x.quoted::<DB>())
+            .collect::<Vec<_>>();
+
+        builder.push(format!(
+            "INSERT INTO {} ({}) VALUES ",
+            self.table.quoted::<DB>(),
+            columns.join(",")
+        ));
+
+        for (i, row) in rows.iter().enumerate() {
+            builder.push(if i == 0 { "(" } else { ",(" });
+            for (j, value) in row.iter().enumerate() {
+                if j > 0 {
+                    builder.push(",");
+                }
+                match value {
+                    ValueType::None => SqlOperation::bind(builder, ValueType::Null),
+                    _ => SqlOperation::bind(builder, value.clone()),
+                };
+            }
+            builder.push(")");
+        }
+
+        if let Some(on_conflict) = &self.on_conflict {
+            builder.push(on_conflict.to_sql::<DB>(&self.columns));
+        }
+
+        if !returning.is_empty() {
+            builder.push(format!(" RETURNING {}", returning.join(",")));
+        }
+    }
+
+    /// Exécute tous les lots dans une transaction
+    /// # Returns
+    /// nombre de lignes insérées
+    pub async fn execute<DB>(&self, pool: &Pool<DB>) -> Result<u64, sqlx::Error>
+    where
+        DB: Dialect,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+    {
+        let mut tx = pool.begin().await?;
+        let mut count = 0;
+
+        for rows in self.rows.chunks(self.rows_per_batch::<DB>()) {
+            let result = DB::execute_with(&mut *tx, |builder| {
+                self.push_batch(builder, rows, &[]);
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+            count += DB::rows_affected(&result);
+        }
+
+        tx.commit().await?;
+        Ok(count)
+    }
+
+    /// Lignes renvoyées par RETURNING, Postgres et Sqlite uniquement
+    pub async fn fetch_returning<DB, T>(&self, pool: &Pool<DB>) -> Result<Vec<T>, sqlx::Error>
+    where
+        DB: Dialect,
+        T: for<'r> FromRow<'r, DB::Row>,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+    {
+        let returning = self.returning_columns::<DB>();
+
+        if returning.is_empty() {
+            return Err(sqlx::Error::Configuration(
+                "RETURNING is not supported by this database or no column was given".into(),
+            ));
+        }
+
+        self.fetch_rows(pool, &returning)
+            .await?
+            .iter()
+            .map(T::from_row)
+            .collect()
+    }
+
+    /// Ids générés, dans l'ordre des lignes: RETURNING sur Postgres et Sqlite,
+    /// LAST_INSERT_ID() sur MySQL (les ids d'un INSERT multi-lignes y sont consécutifs)
+    /// # Arguments
+    /// * `id` - colonne auto-incrémentée
+    pub async fn insert_ids<DB>(&self, pool: &Pool<DB>, id: &Ident) -> Result<Vec<i64>, sqlx::Error>
+    where
+        DB: Dialect,
+        i64: for<'r> Decode<'r, DB> + Type<DB>,
+        usize: ColumnIndex<DB::Row>,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+    {
+        if DB::SUPPORTS_RETURNING {
+            let returning = [format!("CAST({} AS BIGINT)", id.quoted::<DB>())];
+
+            return self
+                .fetch_rows(pool, &returning)
+                .await?
+                .iter()
+                .map(|row| row.try_get::<i64, _>(0))
+                .collect();
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut ids = vec![];
+
+        for rows in self.rows.chunks(self.rows_per_batch::<DB>()) {
+            let result = DB::execute_with(&mut *tx, |builder| {
+                self.push_batch(builder, rows, &[]);
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+            let first = DB::last_insert_id(&result).ok_or(sqlx::Error::RowNotFound)?;
+            ids.extend((0..DB::rows_affected(&result) as i64).map(|x| first + x));
+        }
+
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    async fn fetch_rows<DB>(
+        &self,
+        pool: &Pool<DB>,
+        returning: &[String],
+    ) -> Result<Vec<DB::Row>, sqlx::Error>
+    where
+        DB: Dialect,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+    {
+        let mut tx = pool.begin().await?;
+        let mut items = vec![];
+
+        for rows in self.rows.chunks(self.rows_per_batch::<DB>()) {
+            let mut batch = DB::fetch_all_with(&mut *tx, |builder| {
+                self.push_batch(builder, rows, returning);
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+            items.append(&mut batch);
+        }
+
+        tx.commit().await?;
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{sqlite::SqlitePoolOptions, MySql, Postgres, Sqlite, SqlitePool};
+
+    use super::*;
+
+    fn builder() -> InsertBuilder {
+        let mut builder = InsertBuilder::new(
+            Ident::new("tiers").unwrap(),
+            vec![Ident::new("code").unwrap(), Ident::new("order").unwrap()],
+        )
+        .with_returning(vec![Ident::new("id").unwrap()]);
+
+        builder.push_row(vec!["a".into(), 1.into()]).unwrap();
+        builder.push_row(vec!["b".into(), ValueType::None]).unwrap();
+        builder.push_row(vec!["c".into(), 3.into()]).unwrap();
+        builder
+    }
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE tiers (id INTEGER PRIMARY KEY AUTOINCREMENT, code TEXT UNIQUE, \"order\" INTEGER)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[test]
+    fn column_count() {
+        let mut builder = builder();
+
+        assert_eq!(
+            builder.push_row(vec!["d".into()]),
+            Err(InsertError::ColumnCount {
+                expected: 2,
+                found: 1
+            })
+        )
+    }
+
+    #[test]
+    fn postgres_returning() {
+        let queries = builder().build::<Postgres>();
+
+        assert_eq!(queries.len(), 1);
+        assert_eq!(
+            queries[0].sql(),
+            "INSERT INTO \"tiers\" (\"code\",\"order\") VALUES ($1,$2),($3,NULL),($4,$5) RETURNING \"id\""
+        )
+    }
+
+    #[test]
+    fn mysql_batches_without_returning() {
+        let queries = builder().with_batch_size(2).build::<MySql>();
+
+        assert_eq!(
+            queries.iter().map(|x| x.sql()).collect::<Vec<_>>(),
+            vec![
+                "INSERT INTO `tiers` (`code`,`order`) VALUES (?,?),(?,NULL)",
+                "INSERT INTO `tiers` (`code`,`order`) VALUES (?,?)"
+            ]
+        )
+    }
+
+    #[test]
+    fn batch_within_bind_limit() {
+        assert_eq!(builder().rows_per_batch::<Sqlite>(), 16_383);
+        assert_eq!(
+            builder()
+                .with_batch_size(100_000)
+                .rows_per_batch::<Sqlite>(),
+            16_383
+        );
+    }
+
+    #[tokio::test]
+    async fn execute() {
+        let pool = pool().await;
+
+        let count = builder().with_batch_size(2).execute(&pool).await.unwrap();
+
+        assert_eq!(count, 3);
+    }
+
+    #[tokio::test]
+    async fn returning_ids() {
+        let pool = pool().await;
+
+        let ids = builder()
+            .with_batch_size(2)
+            .insert_ids(&pool, &Ident::new("id").unwrap())
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM tiers")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let rows: Vec<(i64,)> = builder().fetch_returning(&pool).await.unwrap();
+
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(rows, vec![(4,), (5,), (6,)]);
+    }
+
+    #[tokio::test]
+    async fn upsert() {
+        let pool = pool().await;
+        builder().execute(&pool).await.unwrap();
+
+        let mut upsert = InsertBuilder::new(
+            Ident::new("tiers").unwrap(),
+            vec![Ident::new("code").unwrap(), Ident::new("order").unwrap()],
+        )
+        .with_on_conflict(
+            OnConflict::do_update(
+                vec![Ident::new("code").unwrap()],
+                vec![Ident::new("order").unwrap()],
+            )
+            .unwrap(),
+        );
+        upsert.push_row(vec!["a".into(), 10.into()]).unwrap();
+        upsert.push_row(vec!["d".into(), 4.into()]).unwrap();
+        upsert.execute(&pool).await.unwrap();
+
+        let rows: Vec<(String, Option<i32>)> =
+            sqlx::query_as("SELECT code, \"order\" FROM tiers ORDER BY code")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                ("a".to_string(), Some(10)),
+                ("b".to_string(), None),
+                ("c".to_string(), Some(3)),
+                ("d".to_string(), Some(4))
+            ]
+        )
+    }
+}
@@ -0,0 +1,600 @@
+use std::fmt;
+
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::{
+    dialect::Dialect,
+    operations::{SqlOperation, SqlSink},
+    pagination::PaginationError,
+    sort::{NullsOrder, Sort, SortDirection, SortKey},
+    types::ValueType,
+};
+
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+const TIME_FORMAT: &str = "%H:%M:%S%.f";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CursorError {
+    /// curseur mal formé ou modifié par le client
+    Invalid,
+    /// le curseur ne correspond pas au tri demandé
+    KeyCount { expected: usize, found: usize },
+    /// les colonnes d'un tri par curseur doivent être NOT NULL
+    NullValue,
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "Invalid cursor"),
+            Self::KeyCount { expected, found } => {
+                write!(f, "Cursor has {found} keys, sort expects {expected}")
+            }
+            Self::NullValue => write!(f, "Cursor contains a null value"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorDirection {
+    /// lignes situées après le curseur
+    #[default]
+    Next,
+    /// lignes situées avant le curseur
+    Previous,
+}
+
+/// Position dans un tri: valeurs des clés de tri d'une ligne.
+/// Transmis au client sous forme opaque (Cursor::encode)
+#[derive(Clone, Debug)]
+pub struct Cursor {
+    pub direction: CursorDirection,
+    pub values: Vec<ValueType>,
+}
+
+impl Cursor {
+    pub fn next(values: Vec<ValueType>) -> Self {
+        Self {
+            direction: CursorDirection::Next,
+            values,
+        }
+    }
+
+    pub fn previous(values: Vec<ValueType>) -> Self {
+        Self {
+            direction: CursorDirection::Previous,
+            values,
+        }
+    }
+
+    /// Chaîne hexadécimale, utilisable telle quelle dans une url
+    pub fn encode(&self) -> String {
+        let mut raw = String::from(match self.direction {
+            CursorDirection::Next => "n",
+            CursorDirection::Previous => "p",
+        });
+
+        raw.push_str(&encode_values(&self.values));
+
+        encode_hex(raw.as_bytes())
+    }
+
+    pub fn decode(value: &str) -> Result<Self, CursorError> {
+        let raw = decode_hex(value)
+            .and_then(|x| String::from_utf8(x).ok())
+            .ok_or(CursorError::Invalid)?;
+        let (direction, rest) = match raw.split_at_checked(1) {
+            Some(("n", rest)) => (CursorDirection::Next, rest),
+            Some(("p", rest)) => (CursorDirection::Previous, rest),
+            _ => return Err(CursorError::Invalid),
+        };
+
+        let values = decode_values(rest).ok_or(CursorError::Invalid)?;
+
+        Ok(Self { direction, values })
+    }
+}
+
+/// Chaque valeur est écrite `{tag}{longueur}:{valeur}`, une liste contient ses valeurs encodées
+fn encode_values(values: &[ValueType]) -> String {
+    let mut raw = String::new();
+
+    for value in values {
+        let (tag, payload) = match value {
+            ValueType::String(x) => ('s', x.clone()),
+            ValueType::Int(x) => ('i', x.to_string()),
+            ValueType::BigInt(x) => ('l', x.to_string()),
+            ValueType::Float(x) => ('f', x.to_string()),
+            ValueType::Double(x) => ('g', x.to_string()),
+            #[cfg(feature = "decimal")]
+            ValueType::Decimal(x) => ('m', x.to_string()),
+            #[cfg(feature = "uuid")]
+            ValueType::Uuid(x) => ('q', x.to_string()),
+            #[cfg(feature = "json")]
+            ValueType::Json(x) => ('j', x.to_string()),
+            ValueType::Date(x) => ('d', x.to_string()),
+            ValueType::Time(x) => ('h', x.format(TIME_FORMAT).to_string()),
+            ValueType::DateTime(x) => ('t', x.format(DATETIME_FORMAT).to_string()),
+            ValueType::DateTimeUtc(x) => ('u', x.to_rfc3339()),
+            ValueType::Bool(x) => ('b', x.to_string()),
+            ValueType::Bytes(x) => ('x', encode_hex(x)),
+            ValueType::List(x) => ('a', encode_values(x)),
+            ValueType::Null | ValueType::None => ('z', String::new()),
+        };
+        raw.push_str(&format!("{tag}{}:{payload}", payload.len()));
+    }
+
+    raw
+}
+
+fn decode_values(mut raw: &str) -> Option<Vec<ValueType>> {
+    let mut values = vec![];
+
+    while !raw.is_empty() {
+        let (tag, tail) = raw.split_at_checked(1)?;
+        let (len, tail) = tail.split_once(':')?;
+        let (payload, tail) = tail.split_at_checked(len.parse().ok()?)?;
+
+        values.push(decode_value(tag, payload)?);
+        raw = tail;
+    }
+
+    Some(values)
+}
+
+fn decode_value(tag: &str, payload: &str) -> Option<ValueType> {
+    let value = match tag {
+        "s" => ValueType::String(payload.to_string()),
+        "i" => ValueType::Int(payload.parse().ok()?),
+        "l" => ValueType::BigInt(payload.parse().ok()?),
+        "f" => ValueType::Float(payload.parse().ok()?),
+        "g" => ValueType::Double(payload.parse().ok()?),
+        #[cfg(feature = "decimal")]
+        "m" => ValueType::Decimal(payload.parse().ok()?),
+        #[cfg(feature = "uuid")]
+        "q" => ValueType::Uuid(payload.parse().ok()?),
+        #[cfg(feature = "json")]
+        "j" => ValueType::Json(serde_json::from_str(payload).ok()?),
+        "d" => ValueType::Date(payload.parse::<NaiveDate>().ok()?),
+        "h" => ValueType::Time(NaiveTime::parse_from_str(payload, TIME_FORMAT).ok()?),
+        "t" => ValueType::DateTime(NaiveDateTime::parse_from_str(payload, DATETIME_FORMAT).ok()?),
+        "u" => ValueType::DateTimeUtc(payload.parse::<DateTime<Utc>>().ok()?),
+        "b" => ValueType::Bool(payload.parse().ok()?),
+        "x" => ValueType::Bytes(decode_hex(payload)?),
+        "a" => ValueType::List(decode_values(payload)?),
+        "z" => ValueType::Null,
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Taille de page maximum: une ligne de plus est lue pour savoir s'il reste des lignes
+pub const MAX_KEYSET_LIMIT: i32 = i32::MAX - 1;
+
+/// Pagination par curseur (keyset): pas d'OFFSET, la page commence après la dernière
+/// ligne vue. Le tri doit se terminer par une clé unique (id) et ses colonnes être NOT NULL
+#[derive(Clone, Debug)]
+pub struct KeysetPagination {
+    /// entre 1 et MAX_KEYSET_LIMIT, ramené dans ces bornes à l'écriture de la requête
+    pub limit: i32,
+    pub cursor: Option<Cursor>,
+}
+
+impl KeysetPagination {
+    /// limit est ramené entre 1 et MAX_KEYSET_LIMIT
+    pub fn new(limit: i32) -> Self {
+        Self {
+            limit: limit.clamp(1, MAX_KEYSET_LIMIT),
+            cursor: None,
+        }
+    }
+
+    /// Ramène limit entre 1 et max_limit
+    pub fn clamp(self, max_limit: i32) -> Self {
+        Self {
+            limit: self.limit.clamp(1, max_limit.clamp(1, MAX_KEYSET_LIMIT)),
+            ..self
+        }
+    }
+
+    /// Comme clamp, mais renvoie une erreur au lieu de corriger limit
+    pub fn validate(self, max_limit: i32) -> Result<Self, PaginationError> {
+        let max_limit = max_limit.min(MAX_KEYSET_LIMIT);
+
+        if self.limit < 1 {
+            Err(PaginationError::InvalidPageSize(self.limit))
+        } else if self.limit > max_limit {
+            Err(PaginationError::PageSizeTooLarge {
+                max: max_limit,
+                found: self.limit,
+            })
+        } else {
+            Ok(self)
+        }
+    }
+
+    fn page_size(&self) -> i32 {
+        self.limit.clamp(1, MAX_KEYSET_LIMIT)
+    }
+
+    /// cursor est la valeur next ou previous d'une KeysetPage précédente
+    pub fn with_cursor(self, cursor: Option<&str>) -> Result<Self, CursorError> {
+        let cursor = cursor.map(Cursor::decode).transpose()?;
+
+        Ok(Self { cursor, ..self })
+    }
+
+    fn direction(&self) -> CursorDirection {
+        self.cursor
+            .as_ref()
+            .map(|x| x.direction)
+            .unwrap_or_default()
+    }
+}
+
+/// Ecrit le filtre sur le curseur, l'ORDER BY et " LIMIT " (limit + 1 pour savoir s'il reste des lignes).
+/// Avec un sens de tri uniforme: `AND (a, b) > ($1, $2)` si la base compare les tuples,
+/// sinon `AND (a > ? OR (a = ? AND b > ?))`
+pub(crate) fn push_keyset<S: SqlSink>(
+    builder: &mut S,
+    sort: &Sort,
+    pagination: &KeysetPagination,
+) -> Result<(), CursorError> {
+    let direction = pagination.direction();
+    let keys = match direction {
+        CursorDirection::Next => sort.keys.clone(),
+        CursorDirection::Previous => sort.keys.iter().map(reverse).collect(),
+    };
+
+    if let Some(cursor) = &pagination.cursor {
+        if cursor.values.len() != keys.len() {
+            return Err(CursorError::KeyCount {
+                expected: keys.len(),
+                found: cursor.values.len(),
+            });
+        }
+        if cursor
+            .values
+            .iter()
+            .any(|x| matches!(x, ValueType::Null | ValueType::None))
+        {
+            return Err(CursorError::NullValue);
+        }
+
+        push_predicate(builder, &keys, &cursor.values);
+    }
+
+    builder.push(Sort { keys }.to_sql::<S::Database>());
+    builder.push_value(" LIMIT ", (pagination.page_size() + 1).into());
+
+    Ok(())
+}
+
+fn push_predicate<S: SqlSink>(builder: &mut S, keys: &[SortKey], values: &[ValueType]) {
+    let operator = |key: &SortKey| match key.direction {
+        SortDirection::Asc => " > ",
+        SortDirection::Desc => " < ",
+    };
+    if uses_row_values::<S::Database>(keys) {
+        let columns = keys
+            .iter()
+            .map(|x| x.column.as_column().to_sql::<S::Database>())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        builder.push(format!(" AND ({columns}){}(", operator(&keys[0])));
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                builder.push(", ");
+            }
+            builder.bind(value.clone());
+        }
+        builder.push(")");
+        return;
+    }
+
+    builder.push(" AND (");
+    for (i, key) in keys.iter().enumerate() {
+        if i > 0 {
+            builder.push(" OR (");
+        }
+        for (previous, value) in keys.iter().zip(values).take(i) {
+            builder.push(format!(
+                "{} = ",
+                previous.column.as_column().to_sql::<S::Database>()
+            ));
+            builder.bind(value.clone());
+            builder.push(" AND ");
+        }
+        builder.push(format!(
+            "{}{}",
+            key.column.as_column().to_sql::<S::Database>(),
+            operator(key)
+        ));
+        builder.bind(values[i].clone());
+        if i > 0 {
+            builder.push(")");
+        }
+    }
+    builder.push(")");
+}
+
+/// Comparaison de tuples possible: sens de tri uniforme et base compatible
+fn uses_row_values<DB: Dialect>(keys: &[SortKey]) -> bool {
+    let uniform = keys.windows(2).all(|x| x[0].direction == x[1].direction);
+
+    uniform && DB::SUPPORTS_ROW_VALUES && keys.len() > 1
+}
+
+fn reverse(key: &SortKey) -> SortKey {
+    SortKey {
+        column: key.column.clone(),
+        direction: match key.direction {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        },
+        nulls: key.nulls.map(|x| match x {
+            NullsOrder::First => NullsOrder::Last,
+            NullsOrder::Last => NullsOrder::First,
+        }),
+    }
+}
+
+/// Page de résultats d'une pagination par curseur
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeysetPage<T> {
+    pub items: Vec<T>,
+    /// curseur de la page suivante, None sur la dernière page
+    pub next: Option<String>,
+    /// curseur de la page précédente, None sur la première page
+    pub previous: Option<String>,
+}
+
+impl<T> KeysetPage<T> {
+    /// # Arguments
+    /// * `rows` - lignes lues avec set_keyset_pagination (jusqu'à limit + 1)
+    /// * `pagination` - pagination utilisée pour la requête
+    /// * `keys` - valeurs des clés de tri d'une ligne, dans l'ordre du tri
+    pub fn new<F>(mut rows: Vec<T>, pagination: &KeysetPagination, keys: F) -> Self
+    where
+        F: Fn(&T) -> Vec<ValueType>,
+    {
+        let limit = usize::try_from(pagination.page_size()).unwrap_or(1);
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+
+        let (has_next, has_previous) = match pagination.direction() {
+            CursorDirection::Next => (has_more, pagination.cursor.is_some()),
+            CursorDirection::Previous => {
+                rows.reverse();
+                (true, has_more)
+            }
+        };
+
+        Self {
+            next: rows
+                .last()
+                .filter(|_| has_next)
+                .map(|x| Cursor::next(keys(x)).encode()),
+            previous: rows
+                .first()
+                .filter(|_| has_previous)
+                .map(|x| Cursor::previous(keys(x)).encode()),
+            items: rows,
+        }
+    }
+
+    pub fn map<U, F>(self, f: F) -> KeysetPage<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        KeysetPage {
+            items: self.items.into_iter().map(f).collect(),
+            next: self.next,
+            previous: self.previous,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{sqlite::SqlitePoolOptions, MySql, Postgres, QueryBuilder, Sqlite};
+
+    use super::*;
+    use crate::{recording::RecordingBuilder, sort::SortField};
+
+    const FIELDS: &[SortField] = &[
+        SortField::new("date", "t.date"),
+        SortField::new("id", "t.id"),
+    ];
+
+    #[test]
+    fn encode_decode() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let cursor = Cursor::previous(vec![
+            "a:b".into(),
+            42.into(),
+            date.into(),
+            date.and_hms_milli_opt(10, 5, 0, 120).unwrap().into(),
+            ValueType::BigInt(i64::MAX),
+            NaiveTime::from_hms_opt(23, 59, 1).unwrap().into(),
+            date.and_hms_opt(8, 0, 0).unwrap().and_utc().into(),
+            vec![0u8, 255].into(),
+            vec!["a", "b"].into(),
+        ]);
+
+        let decoded = Cursor::decode(&cursor.encode()).unwrap();
+
+        assert_eq!(decoded.direction, CursorDirection::Previous);
+        assert_eq!(decoded.encode(), cursor.encode());
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(Cursor::decode("zz").err(), Some(CursorError::Invalid));
+        assert_eq!(
+            Cursor::decode("6e73393a61").err(),
+            Some(CursorError::Invalid)
+        );
+    }
+
+    #[test]
+    fn postgres_row_values() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let sort = Sort::parse("-date,-id", FIELDS).unwrap();
+        let pagination = KeysetPagination {
+            limit: 10,
+            cursor: Some(Cursor::next(vec!["2024-01-01".into(), 7.into()])),
+        };
+
+        push_keyset(&mut builder, &sort, &pagination).unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            " AND (t.date, t.id) < ($1, $2) ORDER BY t.date DESC, t.id DESC LIMIT $3"
+        )
+    }
+
+    #[test]
+    fn mysql_expanded() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let sort = Sort::parse("date,id", FIELDS).unwrap();
+        let pagination = KeysetPagination {
+            limit: 10,
+            cursor: Some(Cursor::previous(vec!["2024-01-01".into(), 7.into()])),
+        };
+
+        push_keyset(&mut builder, &sort, &pagination).unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            " AND (t.date < ? OR (t.date = ? AND t.id < ?)) ORDER BY t.date DESC, t.id DESC LIMIT ?"
+        )
+    }
+
+    #[test]
+    fn mixed_directions() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let sort = Sort::parse("-date,id", FIELDS).unwrap();
+        let pagination = KeysetPagination {
+            limit: 10,
+            cursor: Some(Cursor::next(vec!["2024-01-01".into(), 7.into()])),
+        };
+
+        push_keyset(&mut builder, &sort, &pagination).unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            " AND (t.date < $1 OR (t.date = $2 AND t.id > $3)) ORDER BY t.date DESC, t.id ASC LIMIT $4"
+        )
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(KeysetPagination::new(0).limit, 1);
+        assert_eq!(KeysetPagination::new(i32::MAX).limit, MAX_KEYSET_LIMIT);
+        assert_eq!(KeysetPagination::new(500).clamp(100).limit, 100);
+        assert_eq!(
+            KeysetPagination::new(500).validate(100).err(),
+            Some(PaginationError::PageSizeTooLarge {
+                max: 100,
+                found: 500
+            })
+        );
+
+        let sort = Sort::parse("id", FIELDS).unwrap();
+        let pagination = KeysetPagination {
+            limit: -5,
+            cursor: None,
+        };
+        let mut builder = RecordingBuilder::<Postgres>::new("");
+        builder.set_keyset_pagination(&sort, &pagination).unwrap();
+
+        assert_eq!(builder.values(), [ValueType::Int(2)]);
+    }
+
+    #[test]
+    fn cursor_key_count() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let sort = Sort::parse("date,id", FIELDS).unwrap();
+        let pagination = KeysetPagination::new(10)
+            .with_cursor(Some(&Cursor::next(vec![7.into()]).encode()))
+            .unwrap();
+
+        assert_eq!(
+            push_keyset(&mut builder, &sort, &pagination),
+            Err(CursorError::KeyCount {
+                expected: 2,
+                found: 1
+            })
+        )
+    }
+
+    #[tokio::test]
+    async fn browse() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER, date TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'b'), (4, 'c'), (5, 'd')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let sort = Sort::parse("date,id", FIELDS).unwrap();
+
+        let fetch = |cursor: Option<String>| {
+            let pool = pool.clone();
+            let sort = sort.clone();
+            async move {
+                let pagination = KeysetPagination::new(2)
+                    .with_cursor(cursor.as_deref())
+                    .unwrap();
+                let mut builder: QueryBuilder<'_, Sqlite> =
+                    QueryBuilder::new("SELECT t.id, t.date FROM t WHERE 1=1");
+                builder.set_keyset_pagination(&sort, &pagination).unwrap();
+                let rows: Vec<(i32, String)> =
+                    builder.build_query_as().fetch_all(&pool).await.unwrap();
+
+                KeysetPage::new(rows, &pagination, |x| vec![x.1.clone().into(), x.0.into()])
+                    .map(|x| x.0)
+            }
+        };
+
+        let first = fetch(None).await;
+        let second = fetch(first.next.clone()).await;
+        let third = fetch(second.next.clone()).await;
+        let back = fetch(third.previous.clone()).await;
+
+        assert_eq!(first.items, vec![1, 2]);
+        assert_eq!(first.previous, None);
+        assert_eq!(second.items, vec![3, 4]);
+        assert_eq!(third.items, vec![5]);
+        assert_eq!(third.next, None);
+        assert_eq!(back.items, vec![3, 4]);
+        assert!(back.previous.is_some());
+    }
+}
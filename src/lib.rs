@@ -1,13 +1,32 @@
 pub mod databases;
 pub mod date_formatters;
 pub mod date_parsers;
+pub mod debug_sql;
+pub mod delete;
+pub mod dialect;
+pub mod dynamic_row;
+#[cfg(feature = "serde")]
+pub mod filter;
+pub mod ident;
+pub mod in_list;
+pub mod insert;
+pub mod keyset;
 pub mod mysql;
 pub mod one_to_many;
 pub mod operations;
+pub mod paginated_query;
 pub mod pagination;
 pub mod postgres;
+pub mod recording;
+pub mod search;
+pub mod sort;
 pub mod sqlite;
 pub mod types;
+pub mod update;
+pub mod upsert;
 pub mod utils;
 
+// chemins ::sqlx_helpers générés par les macros derive, utilisables dans ce crate
+extern crate self as sqlx_helpers;
+
 pub const DATABASE_URL: &str = "DATABASE_URL";
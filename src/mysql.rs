@@ -1,109 +1,295 @@
-use sqlx::{MySql, QueryBuilder};
+use futures::future::BoxFuture;
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    mysql::{MySqlTypeInfo, MySqlValueRef},
+    types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc},
+    Decode, Encode, MySql, QueryBuilder, Row, Type, TypeInfo, ValueRef,
+};
+
+use crate::{
+    debug_sql::{common_literal, quote_str, tuple_literal},
+    in_list::{push_in_list, push_list, InListError, InOptions},
+    keyset::encode_hex,
+    search::{LikePattern, SearchMode},
+};
+
+use super::{
+    dialect::Dialect,
+    operations::{null_comparison, split_connector, SqlBackend, SqlOperation, SqlSink},
+    types::ValueType,
+};
+
+impl Dialect for MySql {
+    ///protocole: nombre de paramètres sur 16 bits
+    const MAX_BIND_PARAMS: usize = 65_535;
+    const SUPPORTS_NULLS_ORDERING: bool = false;
+    const IDENT_QUOTE: char = '`';
+    const SUPPORTS_RETURNING: bool = false;
+    const SUPPORTS_ON_CONFLICT: bool = false;
+    ///supporté mais sans utilisation de l'index: la forme développée est préférée
+    const SUPPORTS_ROW_VALUES: bool = false;
+    const NUMBERED_PARAMS: bool = false;
+
+    fn last_insert_id(result: &Self::QueryResult) -> Option<i64> {
+        Some(result.last_insert_id() as i64)
+    }
 
-use crate::pagination::Pagination;
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
 
-use super::{operations::SqlOperation, types::ValueType};
+    fn decode_column(row: &Self::Row, index: usize) -> Result<ValueType, sqlx::Error> {
+        row.try_get(index)
+    }
 
-impl<'a> SqlOperation for QueryBuilder<'a, MySql> {
-    fn push_value(&mut self, sql: &str, value: ValueType) {
+    ///les antislashs sont échappés, comme avec le sql_mode par défaut
+    fn literal(value: &ValueType) -> String {
         match value {
-            ValueType::None => {}
-            _ => {
-                self.push(format!("{sql}"));
-                self.bind(value);
-            }
-        };
+            ValueType::String(x) => quote_str(&x.replace('\\', "\\\\")),
+            ValueType::Bool(x) => if *x { "TRUE" } else { "FALSE" }.to_string(),
+            ValueType::Bytes(x) => format!("X'{}'", encode_hex(x)),
+            #[cfg(feature = "json")]
+            ValueType::Json(x) => quote_str(&x.to_string().replace('\\', "\\\\")),
+            ValueType::List(x) => tuple_literal::<Self>(x),
+            _ => common_literal(value).unwrap_or_default(),
+        }
     }
 
-    fn in_str<S>(&mut self, sql: &str, values: &[S])
+    fn execute_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Self::QueryResult, E>>
     where
-        S: Into<String> + Clone,
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c,
     {
-        let types = values
-            .iter()
-            .map(|x| ValueType::String((*x).clone().into()))
-            .collect::<Vec<_>>();
-
-        self.in_value_types(sql, &types);
+        Box::pin(async move {
+            let mut builder = QueryBuilder::new("");
+            build(&mut builder)?;
+            Ok(builder.build().execute(conn).await?)
+        })
     }
 
-    fn in_int(&mut self, sql: &str, values: &[i32]) {
-        let types = values
-            .into_iter()
-            .map(|x| ValueType::Int(*x))
-            .collect::<Vec<_>>();
-
-        self.in_value_types(sql, &types);
+    fn fetch_all_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Vec<Self::Row>, E>>
+    where
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c,
+    {
+        Box::pin(async move {
+            let mut builder = QueryBuilder::new("");
+            build(&mut builder)?;
+            Ok(builder.build().fetch_all(conn).await?)
+        })
     }
+}
 
-    fn in_value_types(&mut self, sql: &str, values: &[ValueType]) {
-        if values.is_empty() {
-            return;
-        }
+impl SqlBackend for MySql {
+    ///ValueType::List est développé en liste IN: " AND code IN" donne " AND code IN (?,?)"
+    fn push_value<S: SqlSink<Database = Self>>(builder: &mut S, sql: &str, value: ValueType) {
+        match value {
+            ValueType::None => {}
+            ValueType::Null => {
+                builder.push(null_comparison(sql));
+            }
+            ValueType::List(values) if values.is_empty() => {}
+            ValueType::List(values) => {
+                let _ = push_in_list(builder, sql, &values, InOptions::unbounded());
+            }
+            _ => {
+                builder.push(sql);
+                builder.bind(value);
+            }
+        };
+    }
 
-        self.push(format!("{sql} ("));
-
-        let mut sep = self.separated(",");
-        for v in values.to_vec() {
-            match v {
-                ValueType::String(x) => sep.push_bind(x),
-                ValueType::Int(x) => sep.push_bind(x),
-                ValueType::Float(x) => sep.push_bind(x),
-                ValueType::Bool(x) => sep.push_bind(x),
-                ValueType::Date(x) => sep.push_bind(x),
-                ValueType::DateTime(x) => sep.push_bind(x),
-                ValueType::None => &mut sep,
-            };
-        }
-        sep.push_unseparated(")");
+    ///InBinding::Array n'est pas supporté: les valeurs sont toujours développées
+    fn in_value_types_with<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        values: &[ValueType],
+        options: InOptions,
+    ) -> Result<(), InListError> {
+        push_in_list(builder, sql, values, options)
     }
 
-    fn like_starts_with(&mut self, sql: &str, value: ValueType) {
+    fn bind<S: SqlSink<Database = Self>>(builder: &mut S, value: ValueType) {
         match value {
-            ValueType::None => {}
-            _ => {
-                self.push(format!("{sql} LIKE CONCAT("));
-                self.bind(value);
-                self.push(",'%')");
+            ValueType::Null => builder.push("NULL"),
+            ValueType::None => builder,
+            ValueType::List(x) => {
+                push_list(builder, &x.iter().collect::<Vec<_>>());
+                builder
             }
+            _ => builder.push_bind(value),
         };
     }
 
-    fn like_within(&mut self, sql: &str, value: ValueType) {
+    /// Sans mode, la sensibilité à la casse dépend de la collation de la colonne.
+    /// Avec un mode, collation explicite, indépendante de celle de la colonne:
+    /// utf8mb4_bin (avec LOWER() pour ignorer la casse) ou utf8mb4_0900_ai_ci (MySQL 8) pour ignorer
+    /// les accents. LIKE compare caractère par caractère: ß, æ, œ et þ ne valent pas ss, ae, oe et th
+    fn push_search<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        value: ValueType,
+        pattern: LikePattern,
+        mode: Option<SearchMode>,
+    ) {
         match value {
             ValueType::None => {}
+            ValueType::Null => {
+                builder.push(format!("{sql} IS NULL"));
+            }
             _ => {
-                self.push(format!("{sql} LIKE CONCAT('%',"));
-                self.bind(value);
-                self.push(",'%')");
+                let (connector, column) = split_connector(sql);
+                let (start, end, escape) = pattern.like();
+                let (fold, close, collation) = match mode {
+                    None => ("", "", ""),
+                    Some(SearchMode::CaseSensitive) => ("", "", " COLLATE utf8mb4_bin"),
+                    Some(SearchMode::CaseInsensitive) => ("LOWER(", ")", " COLLATE utf8mb4_bin"),
+                    Some(SearchMode::AccentInsensitive) => ("", "", " COLLATE utf8mb4_0900_ai_ci"),
+                };
+
+                builder.push(format!(
+                    "{connector}{fold}{column}{close} LIKE {fold}{start}"
+                ));
+                builder.bind(value);
+                builder.push(format!("{end}{close}{collation}{escape}"));
             }
         };
     }
+}
 
-    fn bind(&mut self, value: ValueType) {
-        match value {
-            ValueType::String(x) => self.push_bind(x),
-            ValueType::Int(x) => self.push_bind(x),
-            ValueType::Float(x) => self.push_bind(x),
-            ValueType::Bool(x) => self.push_bind(x),
-            ValueType::Date(x) => self.push_bind(x),
-            ValueType::DateTime(x) => self.push_bind(x),
-            ValueType::None => self,
+/// Type par défaut pour NULL, le type réel est donné par Encode::produces
+impl Type<MySql> for ValueType {
+    fn type_info() -> MySqlTypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+
+    fn compatible(_ty: &MySqlTypeInfo) -> bool {
+        true
+    }
+}
+
+/// Valeur lue selon le type de la colonne, NULL donne ValueType::Null.
+/// Les entiers tenant sur 32 bits donnent Int, les autres BigInt
+impl<'r> Decode<'r, MySql> for ValueType {
+    fn decode(value: MySqlValueRef<'r>) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            return Ok(Self::Null);
+        }
+
+        let type_info = value.type_info().into_owned();
+        let decoded = match type_info.name() {
+            "BOOLEAN" => Self::Bool(<bool as Decode<MySql>>::decode(value)?),
+            "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "YEAR" => {
+                Self::Int(<i32 as Decode<MySql>>::decode(value)?)
+            }
+            // les entiers signés sont lus avec extension de signe: 200 en TINYINT UNSIGNED
+            // donnerait -56, les non signés sont donc lus en u8/u16/u32 puis élargis
+            "TINYINT UNSIGNED" => Self::Int(<u8 as Decode<MySql>>::decode(value)?.into()),
+            "SMALLINT UNSIGNED" => Self::Int(<u16 as Decode<MySql>>::decode(value)?.into()),
+            "MEDIUMINT UNSIGNED" => {
+                Self::Int(i32::try_from(<u32 as Decode<MySql>>::decode(value)?)?)
+            }
+            "INT UNSIGNED" => Self::BigInt(<u32 as Decode<MySql>>::decode(value)?.into()),
+            "BIGINT" => Self::BigInt(<i64 as Decode<MySql>>::decode(value)?),
+            "BIGINT UNSIGNED" => {
+                let x = <u64 as Decode<MySql>>::decode(value)?;
+                Self::BigInt(i64::try_from(x)?)
+            }
+            "FLOAT" => Self::Float(<f32 as Decode<MySql>>::decode(value)?),
+            "DOUBLE" => Self::Double(<f64 as Decode<MySql>>::decode(value)?),
+            #[cfg(feature = "decimal")]
+            "DECIMAL" => Self::Decimal(<rust_decimal::Decimal as Decode<MySql>>::decode(value)?),
+            #[cfg(feature = "json")]
+            "JSON" => Self::Json(<serde_json::Value as Decode<MySql>>::decode(value)?),
+            "CHAR" | "VARCHAR" | "TINYTEXT" | "TEXT" | "MEDIUMTEXT" | "LONGTEXT" | "ENUM"
+            | "SET" => Self::String(<String as Decode<MySql>>::decode(value)?),
+            "DATE" => Self::Date(<NaiveDate as Decode<MySql>>::decode(value)?),
+            "TIME" => Self::Time(<NaiveTime as Decode<MySql>>::decode(value)?),
+            "DATETIME" => Self::DateTime(<NaiveDateTime as Decode<MySql>>::decode(value)?),
+            "TIMESTAMP" => Self::DateTimeUtc(<DateTime<Utc> as Decode<MySql>>::decode(value)?),
+            "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BIT" => {
+                Self::Bytes(<Vec<u8> as Decode<MySql>>::decode(value)?)
+            }
+            name => return Err(format!("unsupported column type: {name}").into()),
         };
+
+        Ok(decoded)
     }
+}
 
-    ///sql est du style: " ORDER BY s.sDateCreation DESC LIMIT "
-    fn set_pagination(&mut self, sql: &str, pagination: Pagination) {
-        self.push(sql);
-        self.push_value(" LIMIT ", (pagination.limit as i32).into());
-        self.push(" OFFSET ");
-        self.push_bind(pagination.get_offset_for_page(pagination.page));
+/// ValueType::List ne peut pas être lié en un seul paramètre: utiliser push_value avec IN
+impl<'q> Encode<'q, MySql> for ValueType {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<IsNull, BoxDynError> {
+        match self {
+            Self::String(x) => <String as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Int(x) => <i32 as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::BigInt(x) => <i64 as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Float(x) => <f32 as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Double(x) => <f64 as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(x) => <rust_decimal::Decimal as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(x) => <uuid::Uuid as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            #[cfg(feature = "json")]
+            Self::Json(x) => <serde_json::Value as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Date(x) => <NaiveDate as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Time(x) => <NaiveTime as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::DateTime(x) => <NaiveDateTime as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::DateTimeUtc(x) => <DateTime<Utc> as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Bool(x) => <bool as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::Bytes(x) => <Vec<u8> as Encode<'q, MySql>>::encode_by_ref(x, buf),
+            Self::List(_) => Err("list values cannot be bound as a single parameter".into()),
+            Self::Null | Self::None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn produces(&self) -> Option<MySqlTypeInfo> {
+        let type_info = match self {
+            Self::String(_) => <String as Type<MySql>>::type_info(),
+            Self::Int(_) => <i32 as Type<MySql>>::type_info(),
+            Self::BigInt(_) => <i64 as Type<MySql>>::type_info(),
+            Self::Float(_) => <f32 as Type<MySql>>::type_info(),
+            Self::Double(_) => <f64 as Type<MySql>>::type_info(),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(_) => <rust_decimal::Decimal as Type<MySql>>::type_info(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => <uuid::Uuid as Type<MySql>>::type_info(),
+            #[cfg(feature = "json")]
+            Self::Json(_) => <serde_json::Value as Type<MySql>>::type_info(),
+            Self::Date(_) => <NaiveDate as Type<MySql>>::type_info(),
+            Self::Time(_) => <NaiveTime as Type<MySql>>::type_info(),
+            Self::DateTime(_) => <NaiveDateTime as Type<MySql>>::type_info(),
+            Self::DateTimeUtc(_) => <DateTime<Utc> as Type<MySql>>::type_info(),
+            Self::Bool(_) => <bool as Type<MySql>>::type_info(),
+            Self::Bytes(_) => <Vec<u8> as Type<MySql>>::type_info(),
+            Self::List(_) | Self::Null | Self::None => return None,
+        };
+
+        Some(type_info)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use sqlx::{Connection, MySqlConnection};
+
     use super::*;
+    use crate::{
+        ident::Ident,
+        in_list::InBinding,
+        pagination::Pagination,
+        recording::RecordingBuilder,
+        search::SearchMode,
+        sort::{NullsOrder, Sort, SortField},
+        types::Nullable,
+    };
     #[test]
     fn string() {
         let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
@@ -141,6 +327,23 @@ mod tests {
         assert_eq!(builder.into_sql(), "")
     }
 
+    #[test]
+    fn ident_columns() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let order = Ident::parse("t.order").unwrap();
+        let code = Ident::new("code").unwrap();
+
+        builder.push_value(order.around(" AND ", "="), 1.into());
+        builder.in_str(code.around(" AND ", " NOT IN"), &["a", "b"]);
+        builder.like_within(code.around(" AND ", ""), "x".into());
+        builder.and_is_null(&order);
+
+        assert_eq!(
+            builder.into_sql(),
+            " AND `t`.`order`=? AND `code` NOT IN (?,?) AND `code` LIKE CONCAT('%',REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!' AND `t`.`order` IS NULL"
+        )
+    }
+
     #[test]
     fn push() {
         let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
@@ -157,7 +360,21 @@ mod tests {
 
         builder.like_starts_with("AND field", name_like.to_string().into());
 
-        assert_eq!(builder.into_sql(), "AND field LIKE CONCAT(?,'%')")
+        assert_eq!(builder.into_sql(), "AND field LIKE CONCAT(REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!'")
+    }
+
+    #[test]
+    fn search_modes() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+
+        builder.like_starts_with_mode(" AND name", "Hél".into(), SearchMode::CaseSensitive);
+        builder.like_within_mode(" AND name", "hél".into(), SearchMode::CaseInsensitive);
+        builder.like_within_mode(" AND name", "hel".into(), SearchMode::AccentInsensitive);
+
+        assert_eq!(
+            builder.sql(),
+            " AND name LIKE CONCAT(REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%') COLLATE utf8mb4_bin ESCAPE '!' AND LOWER(name) LIKE LOWER(CONCAT('%',REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%')) COLLATE utf8mb4_bin ESCAPE '!' AND name LIKE CONCAT('%',REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%') COLLATE utf8mb4_0900_ai_ci ESCAPE '!'"
+        );
     }
 
     #[test]
@@ -178,7 +395,7 @@ mod tests {
 
     #[test]
     fn pagination() {
-        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let mut builder = RecordingBuilder::<MySql>::new("");
         let pagination = Pagination {
             page: 5,
             limit: 10,
@@ -187,6 +404,135 @@ mod tests {
 
         builder.set_pagination(" ORDER BY s.position", pagination);
 
-        assert_eq!(builder.sql(), " ORDER BY s.position LIMIT ? OFFSET ?")
+        assert_eq!(builder.sql(), " ORDER BY s.position LIMIT ? OFFSET ?");
+        assert_eq!(
+            builder.values(),
+            [ValueType::Int(10), ValueType::BigInt(40)]
+        );
+    }
+
+    #[test]
+    fn in_str_array_expanded() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let options = InOptions::new().with_binding(InBinding::Array);
+        builder
+            .in_str_with("AND code IN", &["a", "b"], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND code IN (?,?)")
+    }
+
+    #[test]
+    fn not_in_str_chunked() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let options = InOptions::new().with_chunk_size(2);
+        builder
+            .in_str_with("AND code NOT IN", &["a", "b", "c"], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND (code NOT IN (?,?) AND code NOT IN (?))")
+    }
+
+    #[test]
+    fn in_empty_match_nothing() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        builder.in_str::<&str>("AND code IN", &[]);
+        builder.in_int(" AND id NOT IN", &[]);
+
+        assert_eq!(builder.sql(), "AND 1=0")
+    }
+
+    #[test]
+    fn is_null() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        builder.and_is_null("d.date_livraison");
+        builder.and_is_not_null("d.code");
+
+        assert_eq!(
+            builder.sql(),
+            " AND d.date_livraison IS NULL AND d.code IS NOT NULL"
+        )
+    }
+
+    #[test]
+    fn nullable() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        builder.push_value(" AND a=", Nullable::<i32>::Absent.into());
+        builder.push_value(" AND b <> ", Nullable::<i32>::Null.into());
+        builder.push_value(" AND c=", Nullable::Value(1).into());
+
+        assert_eq!(builder.sql(), " AND b IS NOT NULL AND c=?")
+    }
+
+    #[test]
+    fn sorted_pagination() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let fields = [SortField::new("date", "s.date").with_nulls(NullsOrder::First)];
+        let sort = Sort::parse("-date", &fields).unwrap();
+        let pagination = Pagination::new().with_page(2).with_limit(10);
+
+        builder.set_sorted_pagination(&sort, pagination);
+
+        assert_eq!(
+            builder.sql(),
+            " ORDER BY s.date IS NULL DESC, s.date DESC LIMIT ? OFFSET ?"
+        )
+    }
+
+    #[test]
+    fn quoted_ident() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let column = Ident::parse("c.order").unwrap();
+
+        builder.push_value(&format!(" AND {}=", builder.quote(&column)), 1.into());
+
+        assert_eq!(builder.sql(), " AND `c`.`order`=?")
+    }
+
+    #[test]
+    fn list() {
+        let mut builder: QueryBuilder<'_, MySql> = QueryBuilder::new("");
+        let codes: Option<Vec<&str>> = Some(vec!["a", "b"]);
+        builder.push_value(" AND code IN", codes.into());
+        builder.push_value(" AND id NOT IN", Vec::<i32>::new().into());
+        builder.push_value(" AND city IN", Option::<Vec<String>>::None.into());
+
+        assert_eq!(builder.sql(), " AND code IN (?,?)")
+    }
+
+    #[tokio::test]
+    #[ignore = "nécessite MySQL sur localhost"]
+    async fn decode_unsigned() {
+        let mut conn = MySqlConnection::connect("mysql://root@localhost/tw_gtra")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TEMPORARY TABLE unsigned_values (a TINYINT UNSIGNED, b SMALLINT UNSIGNED, c MEDIUMINT UNSIGNED, d INT UNSIGNED)",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO unsigned_values VALUES (200, 65535, 16777215, 4294967295)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let row = sqlx::query("SELECT a, b, c, d FROM unsigned_values")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        let values = (0..4)
+            .map(|i| MySql::decode_column(&row, i).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            values,
+            [
+                ValueType::Int(200),
+                ValueType::Int(65_535),
+                ValueType::Int(16_777_215),
+                ValueType::BigInt(4_294_967_295),
+            ]
+        )
     }
 }
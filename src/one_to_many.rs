@@ -107,6 +107,7 @@ impl OneToMany {
         first
             .into_iter()
             .map(|(p, b_vec)| {
+                #[allow(clippy::unwrap_or_default)]
                 let c_vec = second_map.remove(&p).unwrap_or_else(Vec::new);
                 (p, b_vec, c_vec)
             })
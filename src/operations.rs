@@ -1,16 +1,386 @@
-use crate::pagination::Pagination;
+use std::fmt::Display;
+
+use sqlx::{Database, Encode, QueryBuilder, Type};
+
+use crate::{
+    dialect::Dialect,
+    ident::{Column, Ident, SqlFragment},
+    in_list::{InListError, InOptions},
+    keyset::{push_keyset, CursorError, KeysetPagination},
+    pagination::Pagination,
+    search::{LikePattern, SearchMode},
+    sort::Sort,
+};
 
 use super::types::ValueType;
 
+/// Les méthodes in_xxx sans options écrivent `AND 1=0` lorsque la liste est vide,
+/// et rien pour `AND code NOT IN`, voir EmptyInList. Elles ne renvoient pas d'erreur:
+/// une liste vide après une clause autre que IN / NOT IN (`AND code = ANY`) n'écrit rien,
+/// in_xxx_with renvoie alors InListError::Empty.
+/// Elles écrivent la liste quelle que soit sa taille: au-delà de Dialect::MAX_BIND_PARAMS,
+/// la base renvoie l'erreur à l'exécution. Seules in_xxx_with (InListError::TooManyValues)
+/// et fetch_all_in_chunks, qui exécute une requête par paquet, traitent les grandes listes.
+/// Les fragments sql acceptent du texte brut (" AND code=") ou un Ident quoté selon la base
+/// (`code.around(" AND ", "=")`), voir SqlFragment
 pub trait SqlOperation {
-    fn push_value(&mut self, sql: &str, value: ValueType);
-    fn in_str<S>(&mut self, sql: &str, values: &[S])
+    /// ValueType::None n'écrit rien, ValueType::Null écrit IS NULL / IS NOT NULL
+    fn push_value<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType);
+    fn in_str<'s, S>(&mut self, sql: impl Into<SqlFragment<'s>>, values: &[S])
+    where
+        S: Into<String> + Clone,
+    {
+        let _ = self.in_str_with(sql, values, InOptions::unbounded());
+    }
+    fn in_str_with<'s, S>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        values: &[S],
+        options: InOptions,
+    ) -> Result<(), InListError>
     where
         S: Into<String> + Clone;
-    fn in_int(&mut self, sql: &str, values: &[i32]);
-    fn in_value_types(&mut self, sql: &str, values: &[ValueType]);
+    fn in_int<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, values: &[i32]) {
+        let _ = self.in_int_with(sql, values, InOptions::unbounded());
+    }
+    fn in_int_with<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        values: &[i32],
+        options: InOptions,
+    ) -> Result<(), InListError>;
+    fn in_value_types<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, values: &[ValueType]) {
+        let _ = self.in_value_types_with(sql, values, InOptions::unbounded());
+    }
+    fn in_value_types_with<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        values: &[ValueType],
+        options: InOptions,
+    ) -> Result<(), InListError>;
     fn bind(&mut self, value: ValueType);
-    fn like_starts_with(&mut self, sql: &str, value: ValueType);
-    fn like_within(&mut self, sql: &str, value: ValueType);
+    /// `LIKE 'valeur%'` (ILIKE sur Postgres). Les caractères %, _ et ! de la valeur
+    /// sont échappés avec une clause `ESCAPE '!'`: ils sont recherchés tels quels
+    fn like_starts_with<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType);
+    /// `LIKE '%valeur%'`, valeur échappée
+    fn like_within<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType);
+    /// `LIKE '%valeur'`, valeur échappée
+    fn like_ends_with<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType);
+    /// LIKE sans joker, valeur échappée: égalité avec la sensibilité à la casse du LIKE de la base
+    fn like_exact<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType);
+    /// `LIKE pattern`: % et _ restent des jokers, aucune clause ESCAPE n'est écrite
+    fn like_raw<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, pattern: ValueType);
+    /// like_starts_with avec la même sensibilité à la casse et aux accents sur toutes les bases,
+    /// quelle que soit la collation de la colonne
+    fn like_starts_with_mode<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        value: ValueType,
+        mode: SearchMode,
+    );
+    fn like_within_mode<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        value: ValueType,
+        mode: SearchMode,
+    );
     fn set_pagination(&mut self, sql: &str, pagination: Pagination);
+    /// Identifiant entouré des guillemets de la base, à insérer dans les fragments sql
+    fn quote(&self, ident: &Ident) -> String;
+    fn push_sort(&mut self, sort: &Sort);
+    /// ORDER BY issu de sort suivi de LIMIT / OFFSET
+    fn set_sorted_pagination(&mut self, sort: &Sort, pagination: Pagination);
+    /// Pagination par curseur: filtre sur le curseur, ORDER BY issu de sort puis LIMIT,
+    /// à la place de set_sorted_pagination. Les lignes lues sont passées à KeysetPage::new
+    fn set_keyset_pagination(
+        &mut self,
+        sort: &Sort,
+        pagination: &KeysetPagination,
+    ) -> Result<(), CursorError>;
+
+    fn and_is_null<'c>(&mut self, column: impl Into<Column<'c>>) {
+        self.push_value(column.into().around(" AND ", "="), ValueType::Null);
+    }
+
+    fn and_is_not_null<'c>(&mut self, column: impl Into<Column<'c>>) {
+        self.push_value(column.into().around(" AND ", "<>"), ValueType::Null);
+    }
+}
+
+/// Destination du sql écrit par SqlOperation. Toutes les valeurs liées passent par push_bind:
+/// RecordingBuilder y enregistre exactement ce que le QueryBuilder lie
+pub trait SqlSink {
+    type Database: SqlBackend;
+
+    fn push(&mut self, sql: impl Display) -> &mut Self;
+    fn push_bind(&mut self, value: ValueType) -> &mut Self;
+    fn sql(&self) -> &str;
+}
+
+impl<'a, DB> SqlSink for QueryBuilder<'a, DB>
+where
+    DB: SqlBackend,
+    ValueType: Encode<'a, DB> + Type<DB>,
+{
+    type Database = DB;
+
+    fn push(&mut self, sql: impl Display) -> &mut Self {
+        QueryBuilder::push(self, sql)
+    }
+
+    fn push_bind(&mut self, value: ValueType) -> &mut Self {
+        QueryBuilder::push_bind(self, value)
+    }
+
+    fn sql(&self) -> &str {
+        QueryBuilder::sql(self)
+    }
+}
+
+/// Partie de SqlOperation propre à chaque base, écrite sur n'importe quel SqlSink.
+/// Les fragments sql sont déjà convertis pour la base
+pub trait SqlBackend: Dialect {
+    fn push_value<S: SqlSink<Database = Self>>(builder: &mut S, sql: &str, value: ValueType);
+    fn in_value_types_with<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        values: &[ValueType],
+        options: InOptions,
+    ) -> Result<(), InListError>;
+    fn bind<S: SqlSink<Database = Self>>(builder: &mut S, value: ValueType);
+    /// mode à None pour les méthodes like_xxx sans mode: LIKE (ILIKE sur Postgres) tel quel
+    fn push_search<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        value: ValueType,
+        pattern: LikePattern,
+        mode: Option<SearchMode>,
+    );
+}
+
+impl<B: SqlSink> SqlOperation for B {
+    fn push_value<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType) {
+        B::Database::push_value(self, &sql.into().to_sql::<B::Database>(), value);
+    }
+
+    fn in_str_with<'s, S>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        values: &[S],
+        options: InOptions,
+    ) -> Result<(), InListError>
+    where
+        S: Into<String> + Clone,
+    {
+        let types: Vec<ValueType> = values
+            .iter()
+            .map(|x| ValueType::String((*x).clone().into()))
+            .collect::<Vec<_>>();
+
+        self.in_value_types_with(sql, &types, options)
+    }
+
+    fn in_int_with<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        values: &[i32],
+        options: InOptions,
+    ) -> Result<(), InListError> {
+        let types: Vec<ValueType> = values.iter().map(|x| (*x).into()).collect::<Vec<_>>();
+
+        self.in_value_types_with(sql, &types, options)
+    }
+
+    fn in_value_types_with<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        values: &[ValueType],
+        options: InOptions,
+    ) -> Result<(), InListError> {
+        B::Database::in_value_types_with(self, &sql.into().to_sql::<B::Database>(), values, options)
+    }
+
+    fn bind(&mut self, value: ValueType) {
+        B::Database::bind(self, value);
+    }
+
+    fn like_starts_with<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            value,
+            LikePattern::StartsWith,
+            None,
+        );
+    }
+
+    fn like_within<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            value,
+            LikePattern::Within,
+            None,
+        );
+    }
+
+    fn like_ends_with<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            value,
+            LikePattern::EndsWith,
+            None,
+        );
+    }
+
+    fn like_exact<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, value: ValueType) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            value,
+            LikePattern::Exact,
+            None,
+        );
+    }
+
+    fn like_raw<'s>(&mut self, sql: impl Into<SqlFragment<'s>>, pattern: ValueType) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            pattern,
+            LikePattern::Raw,
+            None,
+        );
+    }
+
+    fn like_starts_with_mode<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        value: ValueType,
+        mode: SearchMode,
+    ) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            value,
+            LikePattern::StartsWith,
+            Some(mode),
+        );
+    }
+
+    fn like_within_mode<'s>(
+        &mut self,
+        sql: impl Into<SqlFragment<'s>>,
+        value: ValueType,
+        mode: SearchMode,
+    ) {
+        B::Database::push_search(
+            self,
+            &sql.into().to_sql::<B::Database>(),
+            value,
+            LikePattern::Within,
+            Some(mode),
+        );
+    }
+
+    ///sql est du style: " ORDER BY s.sDateCreation DESC LIMIT "
+    fn set_pagination(&mut self, sql: &str, pagination: Pagination) {
+        self.push(sql);
+        self.push_value(" LIMIT ", pagination.limit.into());
+        self.push(" OFFSET ");
+        self.push_bind(pagination.get_offset_for_page(pagination.page).into());
+    }
+
+    fn quote(&self, ident: &Ident) -> String {
+        ident.quoted::<B::Database>()
+    }
+
+    fn push_sort(&mut self, sort: &Sort) {
+        self.push(sort.to_sql::<B::Database>());
+    }
+
+    fn set_sorted_pagination(&mut self, sort: &Sort, pagination: Pagination) {
+        self.set_pagination(&sort.to_sql::<B::Database>(), pagination);
+    }
+
+    fn set_keyset_pagination(
+        &mut self,
+        sort: &Sort,
+        pagination: &KeysetPagination,
+    ) -> Result<(), CursorError> {
+        push_keyset(self, sort, pagination)
+    }
+}
+
+/// Ecrit " WHERE 1=1 AND (1=1" puis les filtres et ")": un OR dans les filtres
+/// ne peut pas élargir la requête au-delà des parenthèses
+/// # Returns
+/// false si aucun filtre n'a été écrit (tous les ValueType étaient None, ou liste NOT IN vide),
+/// ou si le premier filtre commence par OR: `1=1 OR ...` ne restreint rien
+pub(crate) fn push_conditions<'a, DB, F>(builder: &mut QueryBuilder<'a, DB>, filters: F) -> bool
+where
+    DB: Database,
+    F: FnOnce(&mut QueryBuilder<'a, DB>),
+{
+    builder.push(" WHERE 1=1 AND (1=1");
+    let len = builder.sql().len();
+    filters(builder);
+    let conditions = builder.sql()[len..].trim_start().to_ascii_uppercase();
+    builder.push(")");
+
+    !conditions.is_empty() && !conditions.starts_with("OR ")
+}
+
+/// Réécrit une comparaison avec ValueType::Null:
+/// "AND field=" devient "AND field IS NULL", "AND field<>" ou "AND field!=" devient
+/// "AND field IS NOT NULL". Tout autre opérateur est comparé au littéral NULL
+pub(crate) fn null_comparison(sql: &str) -> String {
+    let trimmed = sql.trim_end();
+
+    if let Some(left) = trimmed
+        .strip_suffix("<>")
+        .or_else(|| trimmed.strip_suffix("!="))
+    {
+        format!("{} IS NOT NULL", left.trim_end())
+    } else if let Some(left) = trimmed.strip_suffix('=') {
+        format!("{} IS NULL", left.trim_end())
+    } else {
+        format!("{sql}NULL")
+    }
+}
+
+/// " AND s.code" décomposé en connecteur (" AND ") et expression ("s.code").
+/// Connecteurs reconnus: AND, OR et WHERE
+pub(crate) fn split_connector(sql: &str) -> (&str, &str) {
+    let start = sql.len() - sql.trim_start().len();
+    let upper = sql.to_ascii_uppercase();
+
+    let connector_len = ["AND ", "OR ", "WHERE "]
+        .iter()
+        .find(|x| upper[start..].starts_with(*x))
+        .map(|x| start + x.len())
+        .unwrap_or(start);
+
+    (&sql[..connector_len], sql[connector_len..].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_comparisons() {
+        assert_eq!(null_comparison("AND a ="), "AND a IS NULL");
+        assert_eq!(null_comparison("AND a<>"), "AND a IS NOT NULL");
+        assert_eq!(null_comparison("AND a != "), "AND a IS NOT NULL");
+        assert_eq!(null_comparison("AND a > "), "AND a > NULL");
+    }
+
+    #[test]
+    fn connectors() {
+        assert_eq!(split_connector(" AND s.code"), (" AND ", "s.code"));
+        assert_eq!(split_connector(" or name "), (" or ", "name"));
+        assert_eq!(split_connector("s.code"), ("", "s.code"));
+    }
 }
@@ -0,0 +1,257 @@
+use sqlx::{ColumnIndex, Decode, FromRow, Pool, QueryBuilder, Row, Type};
+
+use crate::{dialect::Dialect, operations::SqlOperation, pagination::Pagination};
+
+/// Requête de recherche paginée dont le COUNT est déduit des mêmes filtres.
+/// # Arguments
+/// * `select` - "SELECT t.id, t.name"
+/// * `from` - "FROM tiers t WHERE 1=1", les filtres sont ajoutés à la suite
+#[derive(Clone, Debug)]
+pub struct PaginatedQuery {
+    select: String,
+    from: String,
+    order_by: String,
+    concurrent: bool,
+}
+
+impl PaginatedQuery {
+    pub fn new(select: &str, from: &str) -> Self {
+        Self {
+            select: select.to_string(),
+            from: from.to_string(),
+            order_by: String::new(),
+            concurrent: false,
+        }
+    }
+
+    /// sql est du style: " ORDER BY s.sDateCreation DESC", ou Sort::to_sql
+    pub fn with_order_by(self, sql: &str) -> Self {
+        Self {
+            order_by: sql.to_string(),
+            ..self
+        }
+    }
+
+    /// Exécute le COUNT et la liste en parallèle sur deux connexions du pool.
+    /// Les deux connexions sont demandées en même temps: avec max_connections à 1,
+    /// fetch attend jusqu'au timeout d'acquisition du pool puis échoue
+    pub fn with_concurrency(self, concurrent: bool) -> Self {
+        Self { concurrent, ..self }
+    }
+
+    /// SELECT COUNT(*) FROM (SELECT 1 from filtres) AS count_query.
+    /// Avec DISTINCT dans select, ou GROUP BY / UNION dans from, le select complet est conservé
+    /// pour un COUNT correct: ses colonnes doivent alors avoir des noms uniques
+    /// (`t.id, u.id AS user_id`), MySQL refusant deux colonnes de même nom dans une sous-requête.
+    /// Les filtres ne doivent pas ajouter de GROUP BY
+    pub fn build_count<'a, DB, F>(&self, filters: F) -> QueryBuilder<'a, DB>
+    where
+        DB: Dialect,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        let mut builder = QueryBuilder::new("");
+        self.push_count(&mut builder, filters);
+        builder
+    }
+
+    pub fn build_list<'a, DB, F>(&self, filters: F, pagination: Pagination) -> QueryBuilder<'a, DB>
+    where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        let mut builder = QueryBuilder::new("");
+        self.push_list(&mut builder, filters, pagination);
+        builder
+    }
+
+    /// # Returns
+    /// la page demandée et la pagination avec nb_items renseigné
+    pub async fn fetch<DB, T, F>(
+        &self,
+        pool: &Pool<DB>,
+        pagination: Pagination,
+        filters: F,
+    ) -> Result<(Vec<T>, Pagination), sqlx::Error>
+    where
+        DB: Dialect,
+        T: for<'r> FromRow<'r, DB::Row>,
+        i64: for<'r> Decode<'r, DB> + Type<DB>,
+        usize: ColumnIndex<DB::Row>,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+        F: for<'q> Fn(&mut QueryBuilder<'q, DB>) + Sync,
+    {
+        let (count_rows, rows) = if self.concurrent {
+            let (mut count_conn, mut list_conn) =
+                futures::try_join!(pool.acquire(), pool.acquire())?;
+
+            futures::try_join!(
+                DB::fetch_all_with(&mut *count_conn, |builder| {
+                    self.push_count(builder, &filters);
+                    Ok::<_, sqlx::Error>(())
+                }),
+                DB::fetch_all_with(&mut *list_conn, |builder| {
+                    self.push_list(builder, &filters, pagination);
+                    Ok::<_, sqlx::Error>(())
+                })
+            )?
+        } else {
+            let mut conn = pool.acquire().await?;
+            let count_rows = DB::fetch_all_with(&mut *conn, |builder| {
+                self.push_count(builder, &filters);
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+            let rows = DB::fetch_all_with(&mut *conn, |builder| {
+                self.push_list(builder, &filters, pagination);
+                Ok::<_, sqlx::Error>(())
+            })
+            .await?;
+            (count_rows, rows)
+        };
+
+        let nb_items = match count_rows.first() {
+            Some(row) => row.try_get::<i64, _>(0)?,
+            None => 0,
+        };
+        let items = rows
+            .iter()
+            .map(T::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((
+            items,
+            pagination.with_nb_items(i32::try_from(nb_items).unwrap_or(i32::MAX)),
+        ))
+    }
+
+    fn push_count<'a, DB, F>(&self, builder: &mut QueryBuilder<'a, DB>, filters: F)
+    where
+        DB: Dialect,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        let from = self.from.to_ascii_uppercase();
+        let select = if self.select.to_ascii_uppercase().contains("DISTINCT")
+            || from.contains("GROUP BY")
+            || from.contains("UNION")
+        {
+            self.select.as_str()
+        } else {
+            "SELECT 1"
+        };
+
+        builder.push(format!("SELECT COUNT(*) FROM ({select} {}", self.from));
+        filters(builder);
+        builder.push(") AS count_query");
+    }
+
+    fn push_list<'a, DB, F>(
+        &self,
+        builder: &mut QueryBuilder<'a, DB>,
+        filters: F,
+        pagination: Pagination,
+    ) where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        builder.push(format!("{} {}", self.select, self.from));
+        filters(builder);
+        builder.set_pagination(&self.order_by, pagination);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{sqlite::SqlitePoolOptions, Postgres, SqlitePool};
+
+    use super::*;
+
+    fn query() -> PaginatedQuery {
+        PaginatedQuery::new("SELECT t.id, t.code", "FROM tiers t WHERE 1=1")
+            .with_order_by(" ORDER BY t.id")
+    }
+
+    async fn pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(2)
+            .connect("sqlite:file:paginated_query?mode=memory&cache=shared")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE IF NOT EXISTS tiers (id INTEGER, code TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM tiers")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO tiers VALUES (1, 'a'), (2, 'b'), (3, 'b'), (4, 'b'), (5, 'c')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    #[test]
+    fn count_and_list() {
+        let filters = |b: &mut QueryBuilder<'_, Postgres>| b.push_value(" AND t.code=", "b".into());
+        let pagination = Pagination::new().with_page(2).with_limit(2);
+
+        assert_eq!(
+            query().build_count(filters).sql(),
+            "SELECT COUNT(*) FROM (SELECT 1 FROM tiers t WHERE 1=1 AND t.code=$1) AS count_query"
+        );
+        assert_eq!(
+            query().build_list(filters, pagination).sql(),
+            "SELECT t.id, t.code FROM tiers t WHERE 1=1 AND t.code=$1 ORDER BY t.id LIMIT $2 OFFSET $3"
+        );
+    }
+
+    #[test]
+    fn count_grouped() {
+        let join = PaginatedQuery::new(
+            "SELECT t.id, u.id",
+            "FROM tiers t JOIN users u ON u.tiers_id = t.id WHERE 1=1",
+        );
+        let filters = |_: &mut QueryBuilder<'_, Postgres>| {};
+        let grouped = PaginatedQuery::new(
+            "SELECT t.code, COUNT(*) AS nb",
+            "FROM tiers t GROUP BY t.code",
+        );
+        let distinct = PaginatedQuery::new("SELECT DISTINCT t.code", "FROM tiers t WHERE 1=1");
+
+        assert_eq!(
+            join.build_count(filters).sql(),
+            "SELECT COUNT(*) FROM (SELECT 1 FROM tiers t JOIN users u ON u.tiers_id = t.id WHERE 1=1) AS count_query"
+        );
+        assert_eq!(
+            grouped.build_count(filters).sql(),
+            "SELECT COUNT(*) FROM (SELECT t.code, COUNT(*) AS nb FROM tiers t GROUP BY t.code) AS count_query"
+        );
+        assert_eq!(
+            distinct.build_count(filters).sql(),
+            "SELECT COUNT(*) FROM (SELECT DISTINCT t.code FROM tiers t WHERE 1=1) AS count_query"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch() {
+        let pool = pool().await;
+        let pagination = Pagination::new().with_page(2).with_limit(2);
+
+        for concurrent in [false, true] {
+            let (rows, pagination): (Vec<(i32, String)>, Pagination) = query()
+                .with_concurrency(concurrent)
+                .fetch(&pool, pagination, |b| {
+                    b.push_value(" AND t.code=", "b".into())
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(rows, vec![(4, "b".to_string())]);
+            assert_eq!(pagination.nb_items, 3);
+            assert_eq!(pagination.page_count(), 2);
+        }
+    }
+}
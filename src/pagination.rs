@@ -1,8 +1,50 @@
+use std::fmt;
+
+#[cfg(feature = "derive")]
+pub use sqlx_helpers_derive::PaginatedRequest;
+
 pub trait PaginatedRequest {
     fn get_page(&self) -> Option<i32>;
     fn get_page_size(&self) -> Option<i32>;
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum PaginationError {
+    /// page inférieure à 1
+    InvalidPage(i32),
+    /// taille de page inférieure à 1
+    InvalidPageSize(i32),
+    PageSizeTooLarge {
+        max: i32,
+        found: i32,
+    },
+    /// offset négatif ou qui n'est pas un multiple de la taille de page
+    InvalidOffset {
+        offset: i64,
+        limit: i32,
+    },
+}
+
+impl fmt::Display for PaginationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPage(page) => write!(f, "Invalid page: {page}, must be at least 1"),
+            Self::InvalidPageSize(size) => {
+                write!(f, "Invalid page size: {size}, must be at least 1")
+            }
+            Self::PageSizeTooLarge { max, found } => {
+                write!(f, "Page size {found} exceeds maximum {max}")
+            }
+            Self::InvalidOffset { offset, limit } => write!(
+                f,
+                "Invalid offset: {offset}, must be a positive multiple of the page size {limit}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaginationError {}
+
 #[derive(Clone, Debug, Default, Copy, PartialEq)]
 pub struct Pagination {
     pub page: i32,
@@ -31,22 +73,54 @@ impl Pagination {
         Self { nb_items, ..self }
     }
 
-    pub fn get_offset_for_page(&self, page_index: i32) -> i32 {
+    /// Calculé en i64: (page - 1) * limit peut dépasser i32.
+    /// Une page inférieure à 1 est traitée comme la page 1
+    pub fn get_offset_for_page(&self, page_index: i32) -> i64 {
         if self.limit == i32::MAX {
             0
         } else {
-            (page_index - 1) * self.limit
+            (i64::from(page_index.max(1)) - 1) * i64::from(self.limit.max(0))
         }
     }
 
     pub fn page_count(&self) -> i32 {
         if self.limit == i32::MAX {
             1
+        } else if self.limit <= 0 {
+            0
         } else {
-            (self.nb_items + self.limit - 1) / self.limit
+            let count = (i64::from(self.nb_items.max(0)) + i64::from(self.limit) - 1)
+                / i64::from(self.limit);
+            i32::try_from(count).unwrap_or(i32::MAX)
+        }
+    }
+
+    /// Ramène page et limit dans les bornes: page au moins 1, limit entre 1 et max_limit
+    pub fn clamp(self, max_limit: i32) -> Self {
+        Self {
+            page: self.page.max(1),
+            limit: self.limit.clamp(1, max_limit.max(1)),
+            ..self
         }
     }
 
+    /// Comme clamp, mais renvoie une erreur au lieu de corriger les valeurs
+    pub fn validate(self, max_limit: i32) -> Result<Self, PaginationError> {
+        if self.page < 1 {
+            Err(PaginationError::InvalidPage(self.page))
+        } else if self.limit < 1 {
+            Err(PaginationError::InvalidPageSize(self.limit))
+        } else if self.limit > max_limit {
+            Err(PaginationError::PageSizeTooLarge {
+                max: max_limit,
+                found: self.limit,
+            })
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Les valeurs du client ne sont pas contrôlées, voir from_request_clamped et try_from_request
     pub fn from_request(req: &dyn PaginatedRequest, limit: i32) -> Self {
         Self {
             page: req.get_page().unwrap_or(1),
@@ -54,6 +128,136 @@ impl Pagination {
             ..Default::default()
         }
     }
+
+    /// # Arguments
+    /// * `limit` - taille de page si le client n'en fournit pas
+    /// * `max_limit` - taille de page maximum acceptée
+    pub fn from_request_clamped(req: &dyn PaginatedRequest, limit: i32, max_limit: i32) -> Self {
+        Self::from_request(req, limit).clamp(max_limit)
+    }
+
+    pub fn try_from_request(
+        req: &dyn PaginatedRequest,
+        limit: i32,
+        max_limit: i32,
+    ) -> Result<Self, PaginationError> {
+        Self::from_request(req, limit).validate(max_limit)
+    }
+}
+
+/// Paramètres de pagination d'une query string: `?page=2&page_size=50`,
+/// ou `?limit=50&offset=50`. A utiliser avec les extracteurs Query d'axum ou actix,
+/// puis to_pagination. PaginatedRequest ne voit que page et page_size: offset est ignoré
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<i32>,
+    #[serde(alias = "limit")]
+    pub page_size: Option<i32>,
+    /// utilisé si page est absent, doit être un multiple de la taille de page
+    pub offset: Option<i64>,
+}
+
+#[cfg(feature = "serde")]
+impl PaginatedRequest for PaginationQuery {
+    fn get_page(&self) -> Option<i32> {
+        self.page
+    }
+
+    fn get_page_size(&self) -> Option<i32> {
+        self.page_size
+    }
+}
+
+#[cfg(feature = "serde")]
+impl PaginationQuery {
+    /// Page et taille de page ramenées dans les bornes, comme from_request_clamped.
+    /// Sans page, offset donne la page: il n'est pas arrondi, pour ne pas décaler les lignes
+    /// # Arguments
+    /// * `limit` - taille de page si le client n'en fournit pas
+    /// * `max_limit` - taille de page maximum acceptée
+    /// # Returns
+    /// PaginationError::InvalidOffset si offset n'est pas un multiple positif de la taille de page
+    pub fn to_pagination(&self, limit: i32, max_limit: i32) -> Result<Pagination, PaginationError> {
+        let pagination = Pagination::from_request_clamped(self, limit, max_limit);
+
+        match (self.page, self.offset) {
+            (None, Some(offset)) => {
+                let size = i64::from(pagination.limit);
+                let page = (offset >= 0 && offset % size == 0)
+                    .then(|| i32::try_from(offset / size + 1).ok())
+                    .flatten();
+
+                page.map(|x| pagination.with_page(x))
+                    .ok_or(PaginationError::InvalidOffset {
+                        offset,
+                        limit: pagination.limit,
+                    })
+            }
+            _ => Ok(pagination),
+        }
+    }
+}
+
+/// Page de résultats avec les informations de navigation,
+/// forme commune des réponses paginées
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: i32,
+    pub limit: i32,
+    pub nb_items: i32,
+    pub page_count: i32,
+    pub has_next: bool,
+    pub has_previous: bool,
+}
+
+impl<T> Page<T> {
+    /// pagination doit avoir nb_items renseigné (cf PaginatedQuery::fetch)
+    pub fn new(items: Vec<T>, pagination: Pagination) -> Self {
+        let page_count = pagination.page_count();
+
+        Self {
+            items,
+            page: pagination.page,
+            limit: pagination.limit,
+            nb_items: pagination.nb_items,
+            page_count,
+            has_next: pagination.page < page_count,
+            has_previous: pagination.page > 1,
+        }
+    }
+
+    pub fn pagination(&self) -> Pagination {
+        Pagination {
+            page: self.page,
+            nb_items: self.nb_items,
+            limit: self.limit,
+        }
+    }
+
+    /// Convertit les lignes, par exemple en DTO, sans toucher à la pagination
+    pub fn map<U, F>(self, f: F) -> Page<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            limit: self.limit,
+            nb_items: self.nb_items,
+            page_count: self.page_count,
+            has_next: self.has_next,
+            has_previous: self.has_previous,
+        }
+    }
+}
+
+impl<T> From<(Vec<T>, Pagination)> for Page<T> {
+    fn from((items, pagination): (Vec<T>, Pagination)) -> Self {
+        Self::new(items, pagination)
+    }
 }
 
 #[cfg(test)]
@@ -128,4 +332,187 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn page() {
+        let pagination = Pagination::new()
+            .with_page(2)
+            .with_limit(10)
+            .with_nb_items(35);
+        let page = Page::new(vec![1, 2], pagination);
+
+        assert_eq!(page.page_count, 4);
+        assert!(page.has_next);
+        assert!(page.has_previous);
+        assert_eq!(page.pagination(), pagination);
+    }
+
+    #[test]
+    fn last_page() {
+        let pagination = Pagination::new()
+            .with_page(4)
+            .with_limit(10)
+            .with_nb_items(35);
+        let page: Page<i32> = (vec![], pagination).into();
+
+        assert!(!page.has_next);
+        assert!(page.has_previous);
+    }
+
+    #[test]
+    fn page_without_limit() {
+        let page = Page::new(vec![1, 2, 3], Pagination::new().with_nb_items(3));
+
+        assert_eq!(page.page_count, 1);
+        assert!(!page.has_next);
+        assert!(!page.has_previous);
+    }
+
+    #[test]
+    fn map_page() {
+        let pagination = Pagination::new().with_limit(2).with_nb_items(5);
+        let page = Page::new(vec![1, 2], pagination).map(|x| x.to_string());
+
+        assert_eq!(page.items, vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(page.pagination(), pagination);
+        assert!(page.has_next);
+    }
+
+    #[test]
+    fn clamped_request() {
+        let req = Request {
+            page: Some(0),
+            page_size: Some(1_000_000),
+        };
+        let pagination = Pagination::from_request_clamped(&req, 20, 100);
+
+        assert_eq!(pagination.page, 1);
+        assert_eq!(pagination.limit, 100);
+    }
+
+    #[test]
+    fn rejected_request() {
+        let too_large = Request {
+            page: Some(2),
+            page_size: Some(1_000_000),
+        };
+        let negative_page = Request {
+            page: Some(-1),
+            page_size: None,
+        };
+
+        assert_eq!(
+            Pagination::try_from_request(&too_large, 20, 100),
+            Err(PaginationError::PageSizeTooLarge {
+                max: 100,
+                found: 1_000_000
+            })
+        );
+        assert_eq!(
+            Pagination::try_from_request(&negative_page, 20, 100),
+            Err(PaginationError::InvalidPage(-1))
+        );
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let pagination = Pagination::new().with_page(i32::MAX).with_limit(1_000);
+
+        assert_eq!(
+            pagination.get_offset_for_page(pagination.page),
+            (i64::from(i32::MAX) - 1) * 1_000
+        );
+        assert_eq!(pagination.get_offset_for_page(0), 0);
+    }
+
+    #[test]
+    fn page_count_overflow() {
+        let pagination = Pagination::new().with_limit(10).with_nb_items(i32::MAX);
+
+        assert_eq!(pagination.page_count(), i32::MAX / 10 + 1);
+        assert_eq!(pagination.with_limit(0).page_count(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pagination_query() {
+        let query: PaginationQuery = serde_urlencoded::from_str("page=2&page_size=50").unwrap();
+
+        assert_eq!(
+            query.to_pagination(20, 100),
+            Ok(Pagination::new().with_page(2).with_limit(50))
+        );
+
+        let query: PaginationQuery = serde_urlencoded::from_str("page=-3&page_size=-5").unwrap();
+        assert_eq!(
+            query.to_pagination(20, 100),
+            Ok(Pagination::new().with_page(1).with_limit(1))
+        );
+
+        let query: PaginationQuery = serde_urlencoded::from_str("").unwrap();
+        assert_eq!(
+            query.to_pagination(20, 100),
+            Ok(Pagination::new().with_page(1).with_limit(20))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pagination_query_offset() {
+        let query: PaginationQuery = serde_urlencoded::from_str("limit=50&offset=100").unwrap();
+        let pagination = query.to_pagination(20, 100).unwrap();
+
+        assert_eq!(pagination, Pagination::new().with_page(3).with_limit(50));
+        assert_eq!(pagination.get_offset_for_page(pagination.page), 100);
+
+        let query: PaginationQuery = serde_urlencoded::from_str("offset=40").unwrap();
+        assert_eq!(
+            query.to_pagination(20, 100),
+            Ok(Pagination::new().with_page(3).with_limit(20))
+        );
+
+        let query: PaginationQuery = serde_urlencoded::from_str("limit=50&offset=30").unwrap();
+        assert_eq!(
+            query.to_pagination(20, 100),
+            Err(PaginationError::InvalidOffset {
+                offset: 30,
+                limit: 50
+            })
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_request() {
+        #[derive(PaginatedRequest)]
+        struct Search {
+            page: Option<i32>,
+            page_size: i32,
+        }
+
+        #[derive(PaginatedRequest)]
+        #[pagination(page = "num_page", page_size = "limit")]
+        struct RenamedSearch {
+            num_page: Option<i32>,
+            limit: Option<i32>,
+        }
+
+        let search = Search {
+            page: None,
+            page_size: 10,
+        };
+        let renamed = RenamedSearch {
+            num_page: Some(3),
+            limit: None,
+        };
+
+        assert_eq!(
+            Pagination::from_request(&search, 42),
+            Pagination::new().with_limit(10)
+        );
+        assert_eq!(
+            Pagination::from_request(&renamed, 42),
+            Pagination::new().with_page(3).with_limit(42)
+        );
+    }
 }
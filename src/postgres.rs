@@ -1,106 +1,452 @@
-use sqlx::{Postgres, QueryBuilder};
+use futures::future::BoxFuture;
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{types::Oid, PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc},
+    Decode, Encode, Postgres, QueryBuilder, Row, Type, TypeInfo, ValueRef,
+};
+
+use crate::{
+    debug_sql::{common_literal, quote_str},
+    in_list::{push_in_list, split_in_operator, InBinding, InListError, InOptions},
+    keyset::encode_hex,
+    search::{LikePattern, SearchMode},
+};
+
+use super::{
+    dialect::Dialect,
+    operations::{null_comparison, split_connector, SqlBackend, SqlOperation, SqlSink},
+    types::ValueType,
+};
+
+impl Dialect for Postgres {
+    ///protocole: nombre de paramètres sur 16 bits
+    const MAX_BIND_PARAMS: usize = 65_535;
+    const SUPPORTS_NULLS_ORDERING: bool = true;
+    const IDENT_QUOTE: char = '"';
+    const SUPPORTS_RETURNING: bool = true;
+    const SUPPORTS_ON_CONFLICT: bool = true;
+    const SUPPORTS_ROW_VALUES: bool = true;
+    const NUMBERED_PARAMS: bool = true;
+
+    fn last_insert_id(_result: &Self::QueryResult) -> Option<i64> {
+        None
+    }
+
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
+
+    fn decode_column(row: &Self::Row, index: usize) -> Result<ValueType, sqlx::Error> {
+        row.try_get(index)
+    }
 
-use crate::pagination::Pagination;
+    fn literal(value: &ValueType) -> String {
+        match value {
+            ValueType::String(x) => quote_str(x),
+            ValueType::Bool(x) => if *x { "TRUE" } else { "FALSE" }.to_string(),
+            ValueType::Bytes(x) => format!("'\\x{}'::bytea", encode_hex(x)),
+            #[cfg(feature = "json")]
+            ValueType::Json(x) => format!("{}::jsonb", quote_str(&x.to_string())),
+            ValueType::List(x) => {
+                let values = x.iter().map(Self::literal).collect::<Vec<_>>();
+                format!("ARRAY[{}]", values.join(", "))
+            }
+            _ => common_literal(value).unwrap_or_default(),
+        }
+    }
 
-use super::{operations::SqlOperation, types::ValueType};
+    fn execute_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Self::QueryResult, E>>
+    where
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c,
+    {
+        Box::pin(async move {
+            let mut builder = QueryBuilder::new("");
+            build(&mut builder)?;
+            Ok(builder.build().execute(conn).await?)
+        })
+    }
+
+    fn fetch_all_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Vec<Self::Row>, E>>
+    where
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c,
+    {
+        Box::pin(async move {
+            let mut builder = QueryBuilder::new("");
+            build(&mut builder)?;
+            Ok(builder.build().fetch_all(conn).await?)
+        })
+    }
+}
 
-impl<'a> SqlOperation for QueryBuilder<'a, Postgres> {
-    fn push_value(&mut self, sql: &str, value: ValueType) {
+impl SqlBackend for Postgres {
+    ///ValueType::List est lié en un seul paramètre tableau:
+    ///" AND code IN" donne " AND code = ANY($1)", " AND code = ANY" donne " AND code = ANY($1)",
+    ///" AND tags @> " ou " AND tags && " donne " AND tags @> $1"
+    fn push_value<S: SqlSink<Database = Self>>(builder: &mut S, sql: &str, value: ValueType) {
         match value {
             ValueType::None => {}
+            ValueType::Null => {
+                builder.push(null_comparison(sql));
+            }
+            ValueType::List(values) if values.is_empty() => {}
+            ValueType::List(values) => push_array(builder, sql, values),
             _ => {
-                self.push(format!("{sql}"));
-                self.bind(value);
+                builder.push(sql);
+                builder.bind(value);
             }
         };
     }
 
-    fn like_starts_with(&mut self, sql: &str, value: ValueType) {
+    ///Avec InBinding::Array, "AND code IN" devient "AND code = ANY($1)"
+    ///et "AND code NOT IN" devient "AND code <> ALL($1)".
+    ///Si les valeurs ne sont pas toutes du même type, elles sont développées
+    fn in_value_types_with<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        values: &[ValueType],
+        options: InOptions,
+    ) -> Result<(), InListError> {
+        if options.binding == InBinding::Array && !values.is_empty() {
+            if let (Some((left, negated)), true) =
+                (split_in_operator(sql), PgArray::supports(values))
+            {
+                let operator = if negated { "<> ALL" } else { "= ANY" };
+
+                builder.push(format!("{left} {operator}("));
+                builder.push_bind(ValueType::List(values.to_vec()));
+                builder.push(")");
+                return Ok(());
+            }
+        }
+
+        push_in_list(builder, sql, values, options)
+    }
+
+    fn bind<S: SqlSink<Database = Self>>(builder: &mut S, value: ValueType) {
         match value {
-            ValueType::None => {}
-            _ => {
-                self.push(format!("{sql} ILIKE CONCAT("));
-                self.bind(value);
-                self.push(",'%')");
+            ValueType::Null => builder.push("NULL"),
+            ValueType::None => builder,
+            ValueType::List(x) if !PgArray::supports(&x) => {
+                push_array_literal(builder, x);
+                builder
             }
+            _ => builder.push_bind(value),
         };
     }
 
-    fn like_within(&mut self, sql: &str, value: ValueType) {
+    /// ILIKE, entouré de unaccent() pour SearchMode::AccentInsensitive.
+    /// La valeur est convertie en text ($1::text): REPLACE et ILIKE n'acceptent pas un code numérique
+    fn push_search<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        value: ValueType,
+        pattern: LikePattern,
+        mode: Option<SearchMode>,
+    ) {
         match value {
             ValueType::None => {}
+            ValueType::Null => {
+                builder.push(format!("{sql} IS NULL"));
+            }
             _ => {
-                self.push(format!("{sql} ILIKE CONCAT('%',"));
-                self.bind(value);
-                self.push(",'%')");
+                let (connector, column) = split_connector(sql);
+                let (start, end, escape) = pattern.like();
+                let operator = match mode {
+                    Some(SearchMode::CaseSensitive) => "LIKE",
+                    _ => "ILIKE",
+                };
+                let (fold, close) = match mode {
+                    Some(SearchMode::AccentInsensitive) => ("unaccent(", ")"),
+                    _ => ("", ""),
+                };
+
+                builder.push(format!(
+                    "{connector}{fold}{column}{close} {operator} {fold}{start}"
+                ));
+                builder.bind(value);
+                builder.push(format!("::text{end}{close}{escape}"));
             }
         };
     }
+}
 
-    fn in_str<S>(&mut self, sql: &str, values: &[S])
-    where
-        S: Into<String> + Clone,
-    {
-        let types: Vec<ValueType> = values
-            .iter()
-            .map(|x| ValueType::String((*x).clone().into()))
-            .collect::<Vec<_>>();
+fn push_array<S: SqlSink<Database = Postgres>>(builder: &mut S, sql: &str, values: Vec<ValueType>) {
+    let upper = sql.trim_end().to_ascii_uppercase();
+
+    if split_in_operator(sql).is_some() {
+        let options = InOptions::unbounded().with_binding(InBinding::Array);
+        let _ = builder.in_value_types_with(sql, &values, options);
+    } else if upper.ends_with("ANY") || upper.ends_with("ALL") {
+        builder.push(format!("{}(", sql.trim_end()));
+        builder.bind(ValueType::List(values));
+        builder.push(")");
+    } else {
+        builder.push(sql);
+        builder.bind(ValueType::List(values));
+    }
+}
 
-        self.in_value_types(sql, &types);
+/// ARRAY[$1, $2], pour les types sans tableau lié ou les listes hétérogènes
+fn push_array_literal<S: SqlSink<Database = Postgres>>(builder: &mut S, values: Vec<ValueType>) {
+    builder.push("ARRAY[");
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            builder.push(", ");
+        }
+        builder.bind(value);
     }
+    builder.push("]");
+}
 
-    fn in_int(&mut self, sql: &str, values: &[i32]) {
-        let types: Vec<ValueType> = values.into_iter().map(|x| (*x).into()).collect::<Vec<_>>();
+/// Type inconnu (oid 0): Postgres le déduit de la requête, utilisé pour NULL
+impl Type<Postgres> for ValueType {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_oid(Oid(0))
+    }
 
-        self.in_value_types(sql, &types);
+    fn compatible(_ty: &PgTypeInfo) -> bool {
+        true
     }
+}
 
-    fn in_value_types(&mut self, sql: &str, values: &[ValueType]) {
-        if values.is_empty() {
-            return;
+/// Valeur lue selon le type de la colonne, NULL donne ValueType::Null
+impl<'r> Decode<'r, Postgres> for ValueType {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            return Ok(Self::Null);
         }
 
-        self.push(format!("{sql} ("));
-
-        let mut sep = self.separated(",");
-        for v in values.to_vec() {
-            match v {
-                ValueType::String(x) => sep.push_bind(x),
-                ValueType::Int(x) => sep.push_bind(x),
-                ValueType::Float(x) => sep.push_bind(x),
-                ValueType::Bool(x) => sep.push_bind(x),
-                ValueType::Date(x) => sep.push_bind(x),
-                ValueType::DateTime(x) => sep.push_bind(x),
-                ValueType::None => &mut sep,
-            };
+        let type_info = value.type_info().into_owned();
+        let decoded = match type_info.name() {
+            "BOOL" => Self::Bool(<bool as Decode<Postgres>>::decode(value)?),
+            "INT2" => Self::Int(<i16 as Decode<Postgres>>::decode(value)?.into()),
+            "INT4" => Self::Int(<i32 as Decode<Postgres>>::decode(value)?),
+            "INT8" => Self::BigInt(<i64 as Decode<Postgres>>::decode(value)?),
+            "FLOAT4" => Self::Float(<f32 as Decode<Postgres>>::decode(value)?),
+            "FLOAT8" => Self::Double(<f64 as Decode<Postgres>>::decode(value)?),
+            #[cfg(feature = "decimal")]
+            "NUMERIC" => Self::Decimal(<rust_decimal::Decimal as Decode<Postgres>>::decode(value)?),
+            #[cfg(feature = "uuid")]
+            "UUID" => Self::Uuid(<uuid::Uuid as Decode<Postgres>>::decode(value)?),
+            #[cfg(feature = "json")]
+            "JSON" | "JSONB" => Self::Json(<serde_json::Value as Decode<Postgres>>::decode(value)?),
+            "TEXT" | "VARCHAR" | "CHAR" | "NAME" => {
+                Self::String(<String as Decode<Postgres>>::decode(value)?)
+            }
+            "DATE" => Self::Date(<NaiveDate as Decode<Postgres>>::decode(value)?),
+            "TIME" => Self::Time(<NaiveTime as Decode<Postgres>>::decode(value)?),
+            "TIMESTAMP" => Self::DateTime(<NaiveDateTime as Decode<Postgres>>::decode(value)?),
+            "TIMESTAMPTZ" => Self::DateTimeUtc(<DateTime<Utc> as Decode<Postgres>>::decode(value)?),
+            "BYTEA" => Self::Bytes(<Vec<u8> as Decode<Postgres>>::decode(value)?),
+            "TEXT[]" | "VARCHAR[]" | "CHAR[]" => {
+                Self::list(<Vec<String> as Decode<Postgres>>::decode(value)?)
+            }
+            "INT4[]" => Self::list(<Vec<i32> as Decode<Postgres>>::decode(value)?),
+            "INT8[]" => Self::list(<Vec<i64> as Decode<Postgres>>::decode(value)?),
+            "FLOAT8[]" => Self::list(<Vec<f64> as Decode<Postgres>>::decode(value)?),
+            "BOOL[]" => Self::list(<Vec<bool> as Decode<Postgres>>::decode(value)?),
+            "DATE[]" => Self::list(<Vec<NaiveDate> as Decode<Postgres>>::decode(value)?),
+            "TIMESTAMP[]" => Self::list(<Vec<NaiveDateTime> as Decode<Postgres>>::decode(value)?),
+            #[cfg(feature = "uuid")]
+            "UUID[]" => Self::list(<Vec<uuid::Uuid> as Decode<Postgres>>::decode(value)?),
+            name => return Err(format!("unsupported column type: {name}").into()),
+        };
+
+        Ok(decoded)
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for ValueType {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        match self {
+            Self::String(x) => <String as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Int(x) => <i32 as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::BigInt(x) => <i64 as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Float(x) => <f32 as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Double(x) => <f64 as Encode<Postgres>>::encode_by_ref(x, buf),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(x) => <rust_decimal::Decimal as Encode<Postgres>>::encode_by_ref(x, buf),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(x) => <uuid::Uuid as Encode<Postgres>>::encode_by_ref(x, buf),
+            #[cfg(feature = "json")]
+            Self::Json(x) => <serde_json::Value as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Date(x) => <NaiveDate as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Time(x) => <NaiveTime as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::DateTime(x) => <NaiveDateTime as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::DateTimeUtc(x) => <DateTime<Utc> as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Bool(x) => <bool as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::Bytes(x) => <Vec<u8> as Encode<Postgres>>::encode_by_ref(x, buf),
+            Self::List(x) => PgArray::from_values(x)
+                .ok_or("list values must all have the same array type")?
+                .encode(buf),
+            Self::Null | Self::None => Ok(IsNull::Yes),
         }
-        sep.push_unseparated(")");
     }
 
-    fn bind(&mut self, value: ValueType) {
-        match value {
-            ValueType::String(x) => self.push_bind(x),
-            ValueType::Int(x) => self.push_bind(x),
-            ValueType::Float(x) => self.push_bind(x),
-            ValueType::Bool(x) => self.push_bind(x),
-            ValueType::Date(x) => self.push_bind(x),
-            ValueType::DateTime(x) => self.push_bind(x),
-            ValueType::None => self,
+    fn produces(&self) -> Option<PgTypeInfo> {
+        let type_info = match self {
+            Self::String(_) => <String as Type<Postgres>>::type_info(),
+            Self::Int(_) => <i32 as Type<Postgres>>::type_info(),
+            Self::BigInt(_) => <i64 as Type<Postgres>>::type_info(),
+            Self::Float(_) => <f32 as Type<Postgres>>::type_info(),
+            Self::Double(_) => <f64 as Type<Postgres>>::type_info(),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(_) => <rust_decimal::Decimal as Type<Postgres>>::type_info(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => <uuid::Uuid as Type<Postgres>>::type_info(),
+            #[cfg(feature = "json")]
+            Self::Json(_) => <serde_json::Value as Type<Postgres>>::type_info(),
+            Self::Date(_) => <NaiveDate as Type<Postgres>>::type_info(),
+            Self::Time(_) => <NaiveTime as Type<Postgres>>::type_info(),
+            Self::DateTime(_) => <NaiveDateTime as Type<Postgres>>::type_info(),
+            Self::DateTimeUtc(_) => <DateTime<Utc> as Type<Postgres>>::type_info(),
+            Self::Bool(_) => <bool as Type<Postgres>>::type_info(),
+            Self::Bytes(_) => <Vec<u8> as Type<Postgres>>::type_info(),
+            Self::List(x) => PgArray::from_values(x)?.type_info(),
+            Self::Null | Self::None => return None,
         };
+
+        Some(type_info)
     }
+}
+
+/// Valeurs liables en un seul paramètre tableau
+enum PgArray {
+    Strings(Vec<String>),
+    Ints(Vec<i32>),
+    BigInts(Vec<i64>),
+    Doubles(Vec<f64>),
+    Bools(Vec<bool>),
+    Dates(Vec<NaiveDate>),
+    DateTimes(Vec<NaiveDateTime>),
+    #[cfg(feature = "uuid")]
+    Uuids(Vec<uuid::Uuid>),
+}
+
+impl PgArray {
+    /// false si la liste est vide ou si les valeurs ne sont pas toutes du même type
+    fn supports(values: &[ValueType]) -> bool {
+        let Some(first) = values.first() else {
+            return false;
+        };
+        let supported = match first {
+            ValueType::String(_)
+            | ValueType::Int(_)
+            | ValueType::BigInt(_)
+            | ValueType::Double(_)
+            | ValueType::Bool(_)
+            | ValueType::Date(_)
+            | ValueType::DateTime(_) => true,
+            #[cfg(feature = "uuid")]
+            ValueType::Uuid(_) => true,
+            _ => false,
+        };
 
-    ///sql est du style: " ORDER BY s.sDateCreation DESC LIMIT "
-    fn set_pagination(&mut self, sql: &str, pagination: Pagination) {
-        self.push(sql);
-        self.push_value(" LIMIT ", (pagination.limit as i32).into());
-        self.push(" OFFSET ");
-        self.push_bind(pagination.get_offset_for_page(pagination.page));
+        supported
+            && values
+                .iter()
+                .all(|x| std::mem::discriminant(x) == std::mem::discriminant(first))
+    }
+
+    fn from_values(values: &[ValueType]) -> Option<Self> {
+        if !Self::supports(values) {
+            return None;
+        }
+
+        let array = match values.first()? {
+            ValueType::String(_) => Self::Strings(collect(values, |x| match x {
+                ValueType::String(s) => Some(s.clone()),
+                _ => None,
+            })?),
+            ValueType::Int(_) => Self::Ints(collect(values, |x| match x {
+                ValueType::Int(i) => Some(*i),
+                _ => None,
+            })?),
+            ValueType::BigInt(_) => Self::BigInts(collect(values, |x| match x {
+                ValueType::BigInt(i) => Some(*i),
+                _ => None,
+            })?),
+            ValueType::Double(_) => Self::Doubles(collect(values, |x| match x {
+                ValueType::Double(d) => Some(*d),
+                _ => None,
+            })?),
+            ValueType::Bool(_) => Self::Bools(collect(values, |x| match x {
+                ValueType::Bool(b) => Some(*b),
+                _ => None,
+            })?),
+            ValueType::Date(_) => Self::Dates(collect(values, |x| match x {
+                ValueType::Date(d) => Some(*d),
+                _ => None,
+            })?),
+            ValueType::DateTime(_) => Self::DateTimes(collect(values, |x| match x {
+                ValueType::DateTime(d) => Some(*d),
+                _ => None,
+            })?),
+            #[cfg(feature = "uuid")]
+            ValueType::Uuid(_) => Self::Uuids(collect(values, |x| match x {
+                ValueType::Uuid(u) => Some(*u),
+                _ => None,
+            })?),
+            _ => return None,
+        };
+
+        Some(array)
+    }
+
+    fn encode(self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        match self {
+            Self::Strings(x) => <Vec<String> as Encode<Postgres>>::encode(x, buf),
+            Self::Ints(x) => <Vec<i32> as Encode<Postgres>>::encode(x, buf),
+            Self::BigInts(x) => <Vec<i64> as Encode<Postgres>>::encode(x, buf),
+            Self::Doubles(x) => <Vec<f64> as Encode<Postgres>>::encode(x, buf),
+            Self::Bools(x) => <Vec<bool> as Encode<Postgres>>::encode(x, buf),
+            Self::Dates(x) => <Vec<NaiveDate> as Encode<Postgres>>::encode(x, buf),
+            Self::DateTimes(x) => <Vec<NaiveDateTime> as Encode<Postgres>>::encode(x, buf),
+            #[cfg(feature = "uuid")]
+            Self::Uuids(x) => <Vec<uuid::Uuid> as Encode<Postgres>>::encode(x, buf),
+        }
+    }
+
+    fn type_info(&self) -> PgTypeInfo {
+        match self {
+            Self::Strings(_) => <Vec<String> as Type<Postgres>>::type_info(),
+            Self::Ints(_) => <Vec<i32> as Type<Postgres>>::type_info(),
+            Self::BigInts(_) => <Vec<i64> as Type<Postgres>>::type_info(),
+            Self::Doubles(_) => <Vec<f64> as Type<Postgres>>::type_info(),
+            Self::Bools(_) => <Vec<bool> as Type<Postgres>>::type_info(),
+            Self::Dates(_) => <Vec<NaiveDate> as Type<Postgres>>::type_info(),
+            Self::DateTimes(_) => <Vec<NaiveDateTime> as Type<Postgres>>::type_info(),
+            #[cfg(feature = "uuid")]
+            Self::Uuids(_) => <Vec<uuid::Uuid> as Type<Postgres>>::type_info(),
+        }
     }
 }
 
+fn collect<T>(values: &[ValueType], f: impl Fn(&ValueType) -> Option<T>) -> Option<Vec<T>> {
+    values.iter().map(f).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        ident::Ident,
+        pagination::Pagination,
+        recording::RecordingBuilder,
+        search::SearchMode,
+        sort::{NullsOrder, Sort, SortField},
+        types::Nullable,
+    };
 
     #[test]
     fn string() {
@@ -153,7 +499,32 @@ mod tests {
 
         builder.like_starts_with("AND field", name_like.to_string().into());
 
-        assert_eq!(builder.into_sql(), "AND field ILIKE CONCAT($1,'%')")
+        assert_eq!(builder.into_sql(), "AND field ILIKE CONCAT(REPLACE(REPLACE(REPLACE($1::text, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!'")
+    }
+
+    #[test]
+    fn search_modes() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+
+        builder.like_starts_with_mode(" AND name", "Hél".into(), SearchMode::CaseSensitive);
+        builder.like_within_mode(" AND name", "hel".into(), SearchMode::AccentInsensitive);
+
+        assert_eq!(
+            builder.sql(),
+            " AND name LIKE CONCAT(REPLACE(REPLACE(REPLACE($1::text, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!' AND unaccent(name) ILIKE unaccent(CONCAT('%',REPLACE(REPLACE(REPLACE($2::text, '!', '!!'), '%', '!%'), '_', '!_'),'%')) ESCAPE '!'"
+        );
+    }
+
+    #[test]
+    fn search_int() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        builder.like_within(" AND code", 12.into());
+        builder.like_raw(" AND code", 3.into());
+
+        assert_eq!(
+            builder.sql(),
+            " AND code ILIKE CONCAT('%',REPLACE(REPLACE(REPLACE($1::text, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!' AND code ILIKE $2::text"
+        );
     }
 
     #[test]
@@ -182,7 +553,7 @@ mod tests {
 
     #[test]
     fn pagination() {
-        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let mut builder = RecordingBuilder::<Postgres>::new("");
         let pagination = Pagination {
             page: 5,
             limit: 10,
@@ -191,6 +562,147 @@ mod tests {
 
         builder.set_pagination(" ORDER BY s.position", pagination);
 
-        assert_eq!(builder.sql(), " ORDER BY s.position LIMIT $1 OFFSET $2")
+        assert_eq!(builder.sql(), " ORDER BY s.position LIMIT $1 OFFSET $2");
+        assert_eq!(
+            builder.values(),
+            [ValueType::Int(10), ValueType::BigInt(40)]
+        );
+    }
+
+    #[test]
+    fn in_str_array() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let options = InOptions::new().with_binding(InBinding::Array);
+        builder
+            .in_str_with("AND code IN", &["a", "b", "c"], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND code = ANY($1)")
+    }
+
+    #[test]
+    fn not_in_int_array() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let options = InOptions::new().with_binding(InBinding::Array);
+        builder
+            .in_int_with("AND id NOT IN", &[1, 2, 3], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND id <> ALL($1)")
+    }
+
+    #[test]
+    fn in_mixed_types_array_expanded() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let options = InOptions::new().with_binding(InBinding::Array);
+        builder
+            .in_value_types_with("AND code IN", &["a".into(), 1.into()], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND code IN ($1,$2)")
+    }
+
+    #[test]
+    fn in_int_chunked() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let options = InOptions::new().with_chunk_size(2);
+        builder
+            .in_int_with("AND id IN", &[1, 2, 3], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND (id IN ($1,$2) OR id IN ($3))")
+    }
+
+    #[test]
+    fn in_empty_match_nothing() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        builder.in_str::<&str>("AND code IN", &[]);
+        builder.in_int(" AND id NOT IN", &[]);
+
+        assert_eq!(builder.sql(), "AND 1=0")
+    }
+
+    #[test]
+    fn is_null() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        builder.and_is_null("d.date_livraison");
+        builder.and_is_not_null("d.code");
+
+        assert_eq!(
+            builder.sql(),
+            " AND d.date_livraison IS NULL AND d.code IS NOT NULL"
+        )
+    }
+
+    #[test]
+    fn nullable() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        builder.push_value(" AND a=", Nullable::<i32>::Absent.into());
+        builder.push_value(" AND b <> ", Nullable::<i32>::Null.into());
+        builder.push_value(" AND c=", Nullable::Value(1).into());
+
+        assert_eq!(builder.sql(), " AND b IS NOT NULL AND c=$1")
+    }
+
+    #[test]
+    fn sorted_pagination() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let fields = [SortField::new("date", "s.date").with_nulls(NullsOrder::First)];
+        let sort = Sort::parse("-date", &fields).unwrap();
+        let pagination = Pagination::new().with_page(2).with_limit(10);
+
+        builder.set_sorted_pagination(&sort, pagination);
+
+        assert_eq!(
+            builder.sql(),
+            " ORDER BY s.date DESC NULLS FIRST LIMIT $1 OFFSET $2"
+        )
+    }
+
+    #[test]
+    fn quoted_ident() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        let column = Ident::parse("c.order").unwrap();
+
+        builder.push_value(&format!(" AND {}=", builder.quote(&column)), 1.into());
+
+        assert_eq!(builder.sql(), " AND \"c\".\"order\"=$1")
+    }
+
+    #[test]
+    fn list_operators() {
+        let mut builder: QueryBuilder<'_, Postgres> = QueryBuilder::new("");
+        builder.push_value(" AND code IN", vec!["a", "b"].into());
+        builder.push_value(" AND id = ANY", vec![1i64, 2].into());
+        builder.push_value(" AND tags @> ", vec!["x"].into());
+        builder.push_value(" AND tags && ", Vec::<String>::new().into());
+        builder.push_value(
+            " AND mixed && ",
+            ValueType::List(vec![1.into(), "a".into()]),
+        );
+
+        assert_eq!(
+            builder.sql(),
+            " AND code = ANY($1) AND id = ANY($2) AND tags @> $3 AND mixed && ARRAY[$4, $5]"
+        )
+    }
+
+    #[test]
+    fn encode_type_info() {
+        let list = ValueType::from(vec![1i64, 2]);
+
+        assert_eq!(
+            Encode::<Postgres>::produces(&ValueType::Int(1)),
+            Some(<i32 as Type<Postgres>>::type_info())
+        );
+        assert_eq!(
+            Encode::<Postgres>::produces(&list),
+            Some(<Vec<i64> as Type<Postgres>>::type_info())
+        );
+        assert_eq!(Encode::<Postgres>::produces(&ValueType::None), None);
+        assert_eq!(
+            <ValueType as Type<Postgres>>::type_info(),
+            PgTypeInfo::with_oid(Oid(0))
+        );
     }
 }
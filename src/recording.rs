@@ -0,0 +1,215 @@
+use std::fmt::Display;
+
+use sqlx::{Database, QueryBuilder};
+
+use crate::{
+    debug_sql::render_sql,
+    dialect::Dialect,
+    operations::{SqlBackend, SqlSink},
+    types::ValueType,
+};
+
+/// QueryBuilder qui conserve, dans l'ordre, les valeurs liées par les méthodes de SqlOperation,
+/// pour vérifier le sql et les paramètres dans les tests, sans connexion.
+/// Le sql est écrit par le QueryBuilder de la base et chaque valeur est enregistrée
+/// au moment où elle lui est liée (SqlSink::push_bind): les deux sont identiques à la requête exécutée.
+/// Les valeurs liées directement avec push_bind sur into_inner() ne sont pas enregistrées
+pub struct RecordingBuilder<'a, DB: Database> {
+    builder: QueryBuilder<'a, DB>,
+    values: Vec<ValueType>,
+}
+
+impl<'a, DB> RecordingBuilder<'a, DB>
+where
+    DB: Dialect,
+    DB::Arguments<'a>: Default,
+{
+    pub fn new(init: impl Into<String>) -> Self {
+        Self {
+            builder: QueryBuilder::new(init),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<'a, DB: Dialect> RecordingBuilder<'a, DB> {
+    pub fn push(&mut self, sql: impl Display) -> &mut Self {
+        self.builder.push(sql);
+        self
+    }
+
+    pub fn sql(&self) -> &str {
+        self.builder.sql()
+    }
+
+    /// Valeurs liées, dans l'ordre des paramètres
+    pub fn values(&self) -> &[ValueType] {
+        &self.values
+    }
+
+    /// Sql avec les valeurs en littéraux, voir debug_sql::render_sql
+    pub fn render(&self) -> String {
+        render_sql::<DB>(self.sql(), &self.values)
+    }
+
+    pub fn into_inner(self) -> QueryBuilder<'a, DB> {
+        self.builder
+    }
+}
+
+impl<'a, DB> SqlSink for RecordingBuilder<'a, DB>
+where
+    DB: SqlBackend,
+    QueryBuilder<'a, DB>: SqlSink<Database = DB>,
+{
+    type Database = DB;
+
+    fn push(&mut self, sql: impl Display) -> &mut Self {
+        RecordingBuilder::push(self, sql)
+    }
+
+    fn push_bind(&mut self, value: ValueType) -> &mut Self {
+        self.values.push(value.clone());
+        SqlSink::push_bind(&mut self.builder, value);
+        self
+    }
+
+    fn sql(&self) -> &str {
+        RecordingBuilder::sql(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{MySql, Postgres, Sqlite};
+
+    use super::*;
+    use crate::{
+        in_list::InOptions,
+        keyset::{Cursor, KeysetPagination},
+        operations::SqlOperation,
+        pagination::Pagination,
+        sort::{Sort, SortField},
+    };
+
+    #[test]
+    fn postgres() {
+        let mut builder = RecordingBuilder::<Postgres>::new("SELECT * FROM t WHERE 1=1");
+        builder.push_value(" AND code=", "A".into());
+        builder.push_value(" AND name=", ValueType::Null);
+        builder.push_value(" AND id IN", vec![1, 2].into());
+        builder.like_within(" AND label", "x".into());
+        builder.set_pagination(
+            " ORDER BY id",
+            Pagination::new().with_page(3).with_limit(20),
+        );
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM t WHERE 1=1 AND code=$1 AND name IS NULL AND id = ANY($2) AND label ILIKE CONCAT('%',REPLACE(REPLACE(REPLACE($3::text, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!' ORDER BY id LIMIT $4 OFFSET $5"
+        );
+        assert_eq!(
+            builder.values(),
+            [
+                "A".into(),
+                ValueType::list(vec![1, 2]),
+                "x".into(),
+                ValueType::Int(20),
+                ValueType::BigInt(40),
+            ]
+        );
+    }
+
+    /// Un set_pagination qui lierait l'offset avant la limite écrit le même sql:
+    /// seules les valeurs enregistrées le révèlent
+    #[test]
+    fn swapped_pagination() {
+        let pagination = Pagination::new().with_page(3).with_limit(20);
+        let mut builder = RecordingBuilder::<MySql>::new("SELECT * FROM t");
+        builder.set_pagination(" ORDER BY id", pagination);
+
+        let mut swapped = RecordingBuilder::<MySql>::new("SELECT * FROM t");
+        swapped.push(" ORDER BY id LIMIT ");
+        SqlSink::push_bind(
+            &mut swapped,
+            pagination.get_offset_for_page(pagination.page).into(),
+        );
+        swapped.push(" OFFSET ");
+        SqlSink::push_bind(&mut swapped, pagination.limit.into());
+
+        assert_eq!(builder.sql(), swapped.sql());
+        assert_eq!(
+            builder.values(),
+            [ValueType::Int(20), ValueType::BigInt(40)]
+        );
+        assert_eq!(
+            swapped.values(),
+            [ValueType::BigInt(40), ValueType::Int(20)]
+        );
+        assert_eq!(
+            builder.render(),
+            "/* DEBUG ONLY - NOT FOR EXECUTION */ SELECT * FROM t ORDER BY id LIMIT 20 OFFSET 40"
+        );
+    }
+
+    #[test]
+    fn mysql_lists() {
+        let mut builder = RecordingBuilder::<MySql>::new("SELECT * FROM t WHERE 1=1");
+        builder.push_value(" AND id IN", vec![1, 2].into());
+        builder
+            .in_str_with(
+                " AND code NOT IN",
+                &["a", "b", "c"],
+                InOptions::new().with_chunk_size(2),
+            )
+            .unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM t WHERE 1=1 AND id IN (?,?) AND (code NOT IN (?,?) AND code NOT IN (?))"
+        );
+        assert_eq!(
+            builder.values(),
+            [
+                ValueType::Int(1),
+                ValueType::Int(2),
+                "a".into(),
+                "b".into(),
+                "c".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn sqlite_keyset() {
+        let fields = [
+            SortField::new("id", "t.id"),
+            SortField::new("date", "t.date"),
+        ];
+        let sort = Sort::parse("-date,id", &fields).unwrap();
+        let cursor = Cursor::next(vec!["2024-05-01".into(), 7.into()]).encode();
+        let pagination = KeysetPagination::new(10)
+            .with_cursor(Some(&cursor))
+            .unwrap();
+        let mut builder = RecordingBuilder::<Sqlite>::new("SELECT * FROM t WHERE 1=1");
+        builder.set_keyset_pagination(&sort, &pagination).unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM t WHERE 1=1 AND (t.date < ? OR (t.date = ? AND t.id > ?)) ORDER BY t.date DESC, t.id ASC LIMIT ?"
+        );
+        assert_eq!(
+            builder.values(),
+            [
+                "2024-05-01".into(),
+                "2024-05-01".into(),
+                ValueType::Int(7),
+                ValueType::Int(11),
+            ]
+        );
+        assert_eq!(
+            builder.render(),
+            "/* DEBUG ONLY - NOT FOR EXECUTION */ SELECT * FROM t WHERE 1=1 AND (t.date < '2024-05-01' OR (t.date = '2024-05-01' AND t.id > 7)) ORDER BY t.date DESC, t.id ASC LIMIT 11"
+        );
+    }
+}
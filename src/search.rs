@@ -0,0 +1,247 @@
+/// Sensibilité à la casse et aux accents d'une recherche LIKE,
+/// avec les mêmes résultats sur Postgres, MySQL et Sqlite, aux exceptions près
+/// indiquées pour AccentInsensitive
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// "Hél" trouve "Hélène" mais pas "HÉLÈNE"
+    CaseSensitive,
+    /// "hél" trouve "HÉLÈNE" mais pas "Helene"
+    #[default]
+    CaseInsensitive,
+    /// insensible à la casse et aux accents: "helene" trouve "Hélène".
+    /// Postgres: nécessite l'extension unaccent.
+    /// MySQL: colonne en utf8mb4 et MySQL 8 (collation utf8mb4_0900_ai_ci). LIKE y compare
+    /// caractère par caractère: "strasse" ne trouve pas "Straße", ni "oeuvre" "œuvre"
+    /// (ß, æ, œ, þ), contrairement à Postgres et Sqlite.
+    /// Sqlite: lettres des blocs Latin-1 et Latin étendu A uniquement
+    AccentInsensitive,
+}
+
+/// Position de la valeur recherchée dans la colonne
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LikePattern {
+    StartsWith,
+    EndsWith,
+    Within,
+    /// sans joker
+    Exact,
+    /// motif fourni par l'appelant, non échappé
+    Raw,
+}
+
+/// Caractère d'échappement des jokers LIKE: '!' plutôt que '\\', dont le sens
+/// dans les chaînes littérales varie selon la base
+pub(crate) const LIKE_ESCAPE: char = '!';
+
+/// REPLACE autour du paramètre: `!`, `%` et `_` deviennent `!!`, `!%` et `!_`
+const LIKE_ESCAPE_SQL: (&str, &str) = (
+    "REPLACE(REPLACE(REPLACE(",
+    ", '!', '!!'), '%', '!%'), '_', '!_')",
+);
+
+/// REPLACE autour du paramètre pour GLOB: `[`, `*` et `?` deviennent `[[]`, `[*]` et `[?]`
+const GLOB_ESCAPE_SQL: (&str, &str) = (
+    "REPLACE(REPLACE(REPLACE(",
+    ", '[', '[[]'), '*', '[*]'), '?', '[?]')",
+);
+
+impl LikePattern {
+    /// Motif LIKE autour du paramètre, avec les jokers de la valeur échappés (sauf Raw)
+    /// # Returns
+    /// (début, fin) à écrire avant et après le paramètre, puis la clause ESCAPE
+    pub fn like(self) -> (String, String, String) {
+        let (start, end) = self.wrap('%', LIKE_ESCAPE_SQL);
+        let escape = match self {
+            Self::Raw => String::new(),
+            _ => format!(" ESCAPE '{LIKE_ESCAPE}'"),
+        };
+
+        (start, end, escape)
+    }
+
+    /// Motif GLOB (Sqlite, sensible à la casse), sans clause ESCAPE
+    pub fn glob(self) -> (String, String) {
+        self.wrap('*', GLOB_ESCAPE_SQL)
+    }
+
+    fn wrap(self, wildcard: char, (escape, close): (&str, &str)) -> (String, String) {
+        match self {
+            Self::StartsWith => (format!("CONCAT({escape}"), format!("{close},'{wildcard}')")),
+            Self::EndsWith => (format!("CONCAT('{wildcard}',{escape}"), format!("{close})")),
+            Self::Within => (
+                format!("CONCAT('{wildcard}',{escape}"),
+                format!("{close},'{wildcard}')"),
+            ),
+            Self::Exact => (escape.to_string(), close.to_string()),
+            Self::Raw => (String::new(), String::new()),
+        }
+    }
+}
+
+/// Lettres minuscules accentuées des blocs Latin-1 et Latin étendu A, et leur équivalent sans accent.
+/// Les autres caractères ne sont ni mis en minuscules ni privés de leurs accents sur Sqlite
+const ACCENTS: [(char, &str); 95] = [
+    ('ß', "ss"),
+    ('à', "a"),
+    ('á', "a"),
+    ('â', "a"),
+    ('ã', "a"),
+    ('ä', "a"),
+    ('å', "a"),
+    ('æ', "ae"),
+    ('ç', "c"),
+    ('è', "e"),
+    ('é', "e"),
+    ('ê', "e"),
+    ('ë', "e"),
+    ('ì', "i"),
+    ('í', "i"),
+    ('î', "i"),
+    ('ï', "i"),
+    ('ð', "d"),
+    ('ñ', "n"),
+    ('ò', "o"),
+    ('ó', "o"),
+    ('ô', "o"),
+    ('õ', "o"),
+    ('ö', "o"),
+    ('ø', "o"),
+    ('ù', "u"),
+    ('ú', "u"),
+    ('û', "u"),
+    ('ü', "u"),
+    ('ý', "y"),
+    ('þ', "th"),
+    ('ÿ', "y"),
+    ('ā', "a"),
+    ('ă', "a"),
+    ('ą', "a"),
+    ('ć', "c"),
+    ('ĉ', "c"),
+    ('ċ', "c"),
+    ('č', "c"),
+    ('ď', "d"),
+    ('đ', "d"),
+    ('ē', "e"),
+    ('ĕ', "e"),
+    ('ė', "e"),
+    ('ę', "e"),
+    ('ě', "e"),
+    ('ĝ', "g"),
+    ('ğ', "g"),
+    ('ġ', "g"),
+    ('ģ', "g"),
+    ('ĥ', "h"),
+    ('ħ', "h"),
+    ('ĩ', "i"),
+    ('ī', "i"),
+    ('ĭ', "i"),
+    ('į', "i"),
+    ('ı', "i"),
+    ('ĳ', "ij"),
+    ('ĵ', "j"),
+    ('ķ', "k"),
+    ('ĺ', "l"),
+    ('ļ', "l"),
+    ('ľ', "l"),
+    ('ŀ', "l"),
+    ('ł', "l"),
+    ('ń', "n"),
+    ('ņ', "n"),
+    ('ň', "n"),
+    ('ŋ', "n"),
+    ('ō', "o"),
+    ('ŏ', "o"),
+    ('ő', "o"),
+    ('œ', "oe"),
+    ('ŕ', "r"),
+    ('ŗ', "r"),
+    ('ř', "r"),
+    ('ś', "s"),
+    ('ŝ', "s"),
+    ('ş', "s"),
+    ('š', "s"),
+    ('ţ', "t"),
+    ('ť', "t"),
+    ('ŧ', "t"),
+    ('ũ', "u"),
+    ('ū', "u"),
+    ('ŭ', "u"),
+    ('ů', "u"),
+    ('ű', "u"),
+    ('ų', "u"),
+    ('ŵ', "w"),
+    ('ŷ', "y"),
+    ('ź', "z"),
+    ('ż', "z"),
+    ('ž', "z"),
+    ('ſ', "s"),
+];
+
+/// Mise en minuscules pour Sqlite, dont lower() ne traite que l'ASCII:
+/// les majuscules accentuées sont remplacées avant lower().
+/// Avec accents à true, toutes les lettres accentuées sont remplacées par leur équivalent sans accent
+/// # Returns
+/// (début, fin) à écrire autour de l'expression
+pub(crate) fn sqlite_fold(accents: bool) -> (String, String) {
+    let mut start = String::from("lower(");
+    let mut end = String::new();
+
+    for (letter, base) in ACCENTS {
+        let upper = letter.to_uppercase().to_string();
+        let target = if accents {
+            base.to_string()
+        } else {
+            letter.to_string()
+        };
+
+        // ß et ı n'ont pas de majuscule propre (SS, I): l'ASCII reste traité par lower()
+        if upper.chars().count() == 1 && !upper.is_ascii() {
+            start.push_str("replace(");
+            end.push_str(&format!(", '{upper}', '{target}')"));
+        }
+        if accents {
+            start.push_str("replace(");
+            end.push_str(&format!(", '{letter}', '{base}')"));
+        }
+    }
+    end.push(')');
+
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold() {
+        let (start, end) = sqlite_fold(false);
+
+        assert!(start.starts_with("lower(replace("));
+        assert!(end.contains(", 'É', 'é')"));
+        assert!(!end.contains(", 'é', 'e')"));
+        assert!(sqlite_fold(true).1.contains(", 'É', 'e'), 'é', 'e')"));
+        assert!(end.contains(", 'Ł', 'ł')"));
+        assert!(!end.contains("'SS'") && !end.contains("'I'"));
+        assert!(sqlite_fold(true).1.contains(", 'ß', 'ss')"));
+    }
+
+    #[test]
+    fn patterns() {
+        let (start, end, escape) = LikePattern::EndsWith.like();
+
+        assert_eq!(
+            format!("{start}?{end}{escape}"),
+            "CONCAT('%',REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_')) ESCAPE '!'"
+        );
+        assert_eq!(
+            LikePattern::Raw.like(),
+            (String::new(), String::new(), String::new())
+        );
+        assert_eq!(
+            LikePattern::StartsWith.glob().1,
+            ", '[', '[[]'), '*', '[*]'), '?', '[?]'),'*')"
+        );
+    }
+}
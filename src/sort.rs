@@ -0,0 +1,244 @@
+use std::fmt;
+
+use crate::{
+    dialect::Dialect,
+    ident::{Column, ColumnBuf, Ident},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+/// Champ triable exposé aux clients
+/// # Arguments
+/// * `name` - nom public, utilisé dans ?sort=
+/// * `column` - expression SQL correspondante, ex: s.sDateCreation, ou Ident quoté selon la base
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortField<'a> {
+    pub name: &'a str,
+    pub column: Column<'a>,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl<'a> SortField<'a> {
+    pub const fn new(name: &'a str, column: &'a str) -> Self {
+        Self {
+            name,
+            column: Column::Raw(column),
+            nulls: None,
+        }
+    }
+
+    pub const fn ident(name: &'a str, column: &'a Ident) -> Self {
+        Self {
+            name,
+            column: Column::Ident(column),
+            nulls: None,
+        }
+    }
+
+    pub const fn with_nulls(self, nulls: NullsOrder) -> Self {
+        Self {
+            nulls: Some(nulls),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: ColumnBuf,
+    pub direction: SortDirection,
+    pub nulls: Option<NullsOrder>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SortError {
+    UnknownField(String),
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(name) => write!(f, "Unknown sort field: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for SortError {}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sort {
+    pub keys: Vec<SortKey>,
+}
+
+impl Sort {
+    /// format attendu: "-date,name" (- pour DESC, + optionnel pour ASC)
+    /// # Arguments
+    /// * `value` - paramètre sort de la requête
+    /// * `fields` - liste des champs autorisés
+    pub fn parse(value: &str, fields: &[SortField]) -> Result<Self, SortError> {
+        let keys = value
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                let (name, direction) = match x.strip_prefix('-') {
+                    Some(name) => (name, SortDirection::Desc),
+                    None => (x.strip_prefix('+').unwrap_or(x), SortDirection::Asc),
+                };
+
+                fields
+                    .iter()
+                    .find(|f| f.name == name)
+                    .map(|f| SortKey {
+                        column: f.column.into(),
+                        direction,
+                        nulls: f.nulls,
+                    })
+                    .ok_or_else(|| SortError::UnknownField(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { keys })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Tri utilisé si le client n'en a fourni aucun
+    pub fn or(self, default: Sort) -> Self {
+        if self.is_empty() {
+            default
+        } else {
+            self
+        }
+    }
+
+    /// " ORDER BY a DESC, b ASC", chaîne vide si aucun tri.
+    /// NULLS FIRST/LAST est émulé par `a IS NULL` lorsque la base ne le supporte pas
+    pub fn to_sql<DB: Dialect>(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                let column = key.column.as_column().to_sql::<DB>();
+                let direction = match key.direction {
+                    SortDirection::Asc => "ASC",
+                    SortDirection::Desc => "DESC",
+                };
+
+                match (key.nulls, DB::SUPPORTS_NULLS_ORDERING) {
+                    (None, _) => format!("{column} {direction}"),
+                    (Some(NullsOrder::First), true) => {
+                        format!("{column} {direction} NULLS FIRST")
+                    }
+                    (Some(NullsOrder::Last), true) => {
+                        format!("{column} {direction} NULLS LAST")
+                    }
+                    (Some(NullsOrder::First), false) => {
+                        format!("{column} IS NULL DESC, {column} {direction}")
+                    }
+                    (Some(NullsOrder::Last), false) => {
+                        format!("{column} IS NULL ASC, {column} {direction}")
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        format!(" ORDER BY {}", keys.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{MySql, Postgres};
+
+    use super::*;
+
+    const FIELDS: &[SortField] = &[
+        SortField::new("date", "s.sDateCreation").with_nulls(NullsOrder::Last),
+        SortField::new("name", "s.sNom"),
+    ];
+
+    #[test]
+    fn parse() {
+        let sort = Sort::parse("-date, +name", FIELDS).unwrap();
+
+        assert_eq!(
+            sort.keys,
+            vec![
+                SortKey {
+                    column: "s.sDateCreation".to_string().into(),
+                    direction: SortDirection::Desc,
+                    nulls: Some(NullsOrder::Last)
+                },
+                SortKey {
+                    column: "s.sNom".to_string().into(),
+                    direction: SortDirection::Asc,
+                    nulls: None
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_unknown_field() {
+        let sort = Sort::parse("name,password;DROP TABLE", FIELDS);
+
+        assert_eq!(
+            sort,
+            Err(SortError::UnknownField("password;DROP TABLE".to_string()))
+        )
+    }
+
+    #[test]
+    fn empty() {
+        let sort = Sort::parse("", FIELDS).unwrap();
+
+        assert_eq!(sort.to_sql::<Postgres>(), "")
+    }
+
+    #[test]
+    fn postgres_nulls() {
+        let sort = Sort::parse("-date,name", FIELDS).unwrap();
+
+        assert_eq!(
+            sort.to_sql::<Postgres>(),
+            " ORDER BY s.sDateCreation DESC NULLS LAST, s.sNom ASC"
+        )
+    }
+
+    #[test]
+    fn ident_quoted() {
+        let order = Ident::new("order").unwrap();
+        let fields = [SortField::ident("order", &order)];
+        let sort = Sort::parse("-order", &fields).unwrap();
+
+        assert_eq!(sort.to_sql::<MySql>(), " ORDER BY `order` DESC");
+        assert_eq!(sort.to_sql::<Postgres>(), " ORDER BY \"order\" DESC")
+    }
+
+    #[test]
+    fn mysql_nulls_emulated() {
+        let sort = Sort::parse("-date", FIELDS).unwrap();
+
+        assert_eq!(
+            sort.to_sql::<MySql>(),
+            " ORDER BY s.sDateCreation IS NULL ASC, s.sDateCreation DESC"
+        )
+    }
+}
@@ -1,109 +1,287 @@
-use sqlx::{QueryBuilder, Sqlite};
+use futures::future::BoxFuture;
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef},
+    types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc},
+    Column, Decode, Encode, QueryBuilder, Row, Sqlite, Type, TypeInfo, ValueRef,
+};
+
+use crate::{
+    debug_sql::{common_literal, quote_str, tuple_literal},
+    in_list::{push_in_list, push_list, InListError, InOptions},
+    keyset::encode_hex,
+    search::{sqlite_fold, LikePattern, SearchMode},
+};
+
+use super::{
+    dialect::Dialect,
+    operations::{null_comparison, split_connector, SqlBackend, SqlOperation, SqlSink},
+    types::ValueType,
+};
+
+impl Dialect for Sqlite {
+    ///SQLITE_MAX_VARIABLE_NUMBER par défaut depuis 3.32
+    const MAX_BIND_PARAMS: usize = 32_766;
+    const SUPPORTS_NULLS_ORDERING: bool = true;
+    const IDENT_QUOTE: char = '"';
+    const SUPPORTS_RETURNING: bool = true;
+    const SUPPORTS_ON_CONFLICT: bool = true;
+    ///depuis Sqlite 3.15
+    const SUPPORTS_ROW_VALUES: bool = true;
+    const NUMBERED_PARAMS: bool = false;
+
+    fn last_insert_id(result: &Self::QueryResult) -> Option<i64> {
+        Some(result.last_insert_rowid())
+    }
 
-use crate::pagination::Pagination;
+    fn rows_affected(result: &Self::QueryResult) -> u64 {
+        result.rows_affected()
+    }
 
-use super::{operations::SqlOperation, types::ValueType};
+    ///le type déclaré de la colonne (DATE, DATETIME, BOOLEAN...) est prioritaire
+    ///sur le type de stockage
+    fn decode_column(row: &Self::Row, index: usize) -> Result<ValueType, sqlx::Error> {
+        let value = match row.column(index).type_info().name() {
+            "BOOLEAN" => row.try_get::<Option<bool>, _>(index)?.map(ValueType::from),
+            "DATE" => row
+                .try_get::<Option<NaiveDate>, _>(index)?
+                .map(ValueType::from),
+            "TIME" => row
+                .try_get::<Option<NaiveTime>, _>(index)?
+                .map(ValueType::from),
+            "DATETIME" => row
+                .try_get::<Option<NaiveDateTime>, _>(index)?
+                .map(ValueType::from),
+            _ => return row.try_get(index),
+        };
 
-impl<'a> SqlOperation for QueryBuilder<'a, Sqlite> {
-    fn push_value(&mut self, sql: &str, value: ValueType) {
+        Ok(value.unwrap_or(ValueType::Null))
+    }
+
+    fn literal(value: &ValueType) -> String {
         match value {
-            ValueType::None => {}
-            _ => {
-                self.push(format!("{sql}"));
-                self.bind(value);
-            }
-        };
+            ValueType::String(x) => quote_str(x),
+            ValueType::Bool(x) => if *x { "1" } else { "0" }.to_string(),
+            ValueType::Bytes(x) => format!("X'{}'", encode_hex(x)),
+            #[cfg(feature = "decimal")]
+            ValueType::Decimal(x) => quote_str(&x.to_string()),
+            #[cfg(feature = "json")]
+            ValueType::Json(x) => quote_str(&x.to_string()),
+            ValueType::List(x) => tuple_literal::<Self>(x),
+            _ => common_literal(value).unwrap_or_default(),
+        }
     }
 
-    fn in_str<S>(&mut self, sql: &str, values: &[S])
+    fn execute_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Self::QueryResult, E>>
     where
-        S: Into<String> + Clone,
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c,
     {
-        let types = values
-            .iter()
-            .map(|x| ValueType::String((*x).clone().into()))
-            .collect::<Vec<_>>();
-
-        self.in_value_types(sql, &types);
+        Box::pin(async move {
+            let mut builder = QueryBuilder::new("");
+            build(&mut builder)?;
+            Ok(builder.build().execute(conn).await?)
+        })
     }
 
-    fn in_int(&mut self, sql: &str, values: &[i32]) {
-        let types = values
-            .into_iter()
-            .map(|x| ValueType::Int(*x))
-            .collect::<Vec<_>>();
-
-        self.in_value_types(sql, &types);
+    fn fetch_all_with<'c, F, E>(
+        conn: &'c mut Self::Connection,
+        build: F,
+    ) -> BoxFuture<'c, Result<Vec<Self::Row>, E>>
+    where
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, Self>) -> Result<(), E> + Send + 'c,
+        E: From<sqlx::Error> + Send + 'c,
+    {
+        Box::pin(async move {
+            let mut builder = QueryBuilder::new("");
+            build(&mut builder)?;
+            Ok(builder.build().fetch_all(conn).await?)
+        })
     }
+}
 
-    fn in_value_types(&mut self, sql: &str, values: &[ValueType]) {
-        if values.is_empty() {
-            return;
-        }
+impl SqlBackend for Sqlite {
+    ///ValueType::List est développé en liste IN: " AND code IN" donne " AND code IN (?,?)"
+    fn push_value<S: SqlSink<Database = Self>>(builder: &mut S, sql: &str, value: ValueType) {
+        match value {
+            ValueType::None => {}
+            ValueType::Null => {
+                builder.push(null_comparison(sql));
+            }
+            ValueType::List(values) if values.is_empty() => {}
+            ValueType::List(values) => {
+                let _ = push_in_list(builder, sql, &values, InOptions::unbounded());
+            }
+            _ => {
+                builder.push(sql);
+                builder.bind(value);
+            }
+        };
+    }
 
-        self.push(format!("{sql} ("));
-
-        let mut sep = self.separated(",");
-        for v in values.to_vec() {
-            match v {
-                ValueType::String(x) => sep.push_bind(x),
-                ValueType::Int(x) => sep.push_bind(x),
-                ValueType::Float(x) => sep.push_bind(x),
-                ValueType::Bool(x) => sep.push_bind(x),
-                ValueType::Date(x) => sep.push_bind(x),
-                ValueType::DateTime(x) => sep.push_bind(x),
-                ValueType::None => &mut sep,
-            };
-        }
-        sep.push_unseparated(")");
+    ///InBinding::Array n'est pas supporté: les valeurs sont toujours développées
+    fn in_value_types_with<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        values: &[ValueType],
+        options: InOptions,
+    ) -> Result<(), InListError> {
+        push_in_list(builder, sql, values, options)
     }
 
-    fn like_starts_with(&mut self, sql: &str, value: ValueType) {
+    fn bind<S: SqlSink<Database = Self>>(builder: &mut S, value: ValueType) {
         match value {
-            ValueType::None => {}
-            _ => {
-                self.push(format!("{sql} LIKE CONCAT("));
-                self.bind(value);
-                self.push(",'%')");
+            ValueType::Null => builder.push("NULL"),
+            ValueType::None => builder,
+            ValueType::List(x) => {
+                push_list(builder, &x.iter().collect::<Vec<_>>());
+                builder
             }
+            _ => builder.push_bind(value),
         };
     }
 
-    fn like_within(&mut self, sql: &str, value: ValueType) {
+    /// LIKE n'ignore la casse que pour l'ASCII. Avec un mode: GLOB pour une recherche sensible
+    /// à la casse, colonne et valeur passées en minuscules (et sans accents) sinon
+    fn push_search<S: SqlSink<Database = Self>>(
+        builder: &mut S,
+        sql: &str,
+        value: ValueType,
+        pattern: LikePattern,
+        mode: Option<SearchMode>,
+    ) {
         match value {
             ValueType::None => {}
+            ValueType::Null => {
+                builder.push(format!("{sql} IS NULL"));
+            }
             _ => {
-                self.push(format!("{sql} LIKE CONCAT('%',"));
-                self.bind(value);
-                self.push(",'%')");
+                let (connector, column) = split_connector(sql);
+
+                if mode == Some(SearchMode::CaseSensitive) {
+                    let (start, end) = pattern.glob();
+
+                    builder.push(format!("{connector}{column} GLOB {start}"));
+                    builder.bind(value);
+                    builder.push(end);
+                } else {
+                    let (start, end, escape) = pattern.like();
+                    let (fold, close) = match mode {
+                        Some(mode) => sqlite_fold(mode == SearchMode::AccentInsensitive),
+                        None => (String::new(), String::new()),
+                    };
+
+                    builder.push(format!(
+                        "{connector}{fold}{column}{close} LIKE {fold}{start}"
+                    ));
+                    builder.bind(value);
+                    builder.push(format!("{end}{close}{escape}"));
+                }
             }
         };
     }
+}
 
-    fn bind(&mut self, value: ValueType) {
-        match value {
-            ValueType::String(x) => self.push_bind(x),
-            ValueType::Int(x) => self.push_bind(x),
-            ValueType::Float(x) => self.push_bind(x),
-            ValueType::Bool(x) => self.push_bind(x),
-            ValueType::Date(x) => self.push_bind(x),
-            ValueType::DateTime(x) => self.push_bind(x),
-            ValueType::None => self,
+/// Type par défaut pour NULL, le type réel est donné par Encode::produces
+impl Type<Sqlite> for ValueType {
+    fn type_info() -> SqliteTypeInfo {
+        <str as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(_ty: &SqliteTypeInfo) -> bool {
+        true
+    }
+}
+
+/// Valeur lue selon son type de stockage: INTEGER, REAL, TEXT ou BLOB.
+/// Les dates et booléens sont lus en String / BigInt, Dialect::decode_column
+/// utilise le type déclaré de la colonne pour les convertir
+impl<'r> Decode<'r, Sqlite> for ValueType {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        if value.is_null() {
+            return Ok(Self::Null);
+        }
+
+        let type_info = value.type_info().into_owned();
+        let decoded = match type_info.name() {
+            "INTEGER" => Self::BigInt(<i64 as Decode<Sqlite>>::decode(value)?),
+            "REAL" => Self::Double(<f64 as Decode<Sqlite>>::decode(value)?),
+            "BLOB" => Self::Bytes(<Vec<u8> as Decode<Sqlite>>::decode(value)?),
+            _ => Self::String(<String as Decode<Sqlite>>::decode(value)?),
         };
+
+        Ok(decoded)
+    }
+}
+
+/// ValueType::List ne peut pas être lié en un seul paramètre: utiliser push_value avec IN
+impl<'q> Encode<'q, Sqlite> for ValueType {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> Result<IsNull, BoxDynError> {
+        match self {
+            Self::String(x) => <String as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Int(x) => <i32 as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::BigInt(x) => <i64 as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Float(x) => <f32 as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Double(x) => <f64 as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(x) => <String as Encode<'q, Sqlite>>::encode(x.to_string(), buf),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(x) => <uuid::Uuid as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            #[cfg(feature = "json")]
+            Self::Json(x) => <serde_json::Value as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Date(x) => <NaiveDate as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Time(x) => <NaiveTime as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::DateTime(x) => <NaiveDateTime as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::DateTimeUtc(x) => <DateTime<Utc> as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Bool(x) => <bool as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::Bytes(x) => <Vec<u8> as Encode<'q, Sqlite>>::encode_by_ref(x, buf),
+            Self::List(_) => Err("list values cannot be bound as a single parameter".into()),
+            Self::Null | Self::None => Ok(IsNull::Yes),
+        }
     }
 
-    ///sql est du style: " ORDER BY s.sDateCreation DESC LIMIT "
-    fn set_pagination(&mut self, sql: &str, pagination: Pagination) {
-        self.push(sql);
-        self.push_value(" LIMIT ", (pagination.limit as i32).into());
-        self.push(" OFFSET ");
-        self.push_bind(pagination.get_offset_for_page(pagination.page));
+    fn produces(&self) -> Option<SqliteTypeInfo> {
+        let type_info = match self {
+            Self::String(_) => <String as Type<Sqlite>>::type_info(),
+            Self::Int(_) => <i32 as Type<Sqlite>>::type_info(),
+            Self::BigInt(_) => <i64 as Type<Sqlite>>::type_info(),
+            Self::Float(_) => <f32 as Type<Sqlite>>::type_info(),
+            Self::Double(_) => <f64 as Type<Sqlite>>::type_info(),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(_) => <String as Type<Sqlite>>::type_info(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(_) => <uuid::Uuid as Type<Sqlite>>::type_info(),
+            #[cfg(feature = "json")]
+            Self::Json(_) => <serde_json::Value as Type<Sqlite>>::type_info(),
+            Self::Date(_) => <NaiveDate as Type<Sqlite>>::type_info(),
+            Self::Time(_) => <NaiveTime as Type<Sqlite>>::type_info(),
+            Self::DateTime(_) => <NaiveDateTime as Type<Sqlite>>::type_info(),
+            Self::DateTimeUtc(_) => <DateTime<Utc> as Type<Sqlite>>::type_info(),
+            Self::Bool(_) => <bool as Type<Sqlite>>::type_info(),
+            Self::Bytes(_) => <Vec<u8> as Type<Sqlite>>::type_info(),
+            Self::List(_) | Self::Null | Self::None => return None,
+        };
+
+        Some(type_info)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        ident::Ident,
+        in_list::InBinding,
+        pagination::Pagination,
+        recording::RecordingBuilder,
+        search::SearchMode,
+        sort::{NullsOrder, Sort, SortField},
+        types::Nullable,
+    };
     #[test]
     fn string() {
         let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
@@ -157,7 +335,7 @@ mod tests {
 
         builder.like_starts_with("AND field", name_like.to_string().into());
 
-        assert_eq!(builder.into_sql(), "AND field LIKE CONCAT(?,'%')")
+        assert_eq!(builder.into_sql(), "AND field LIKE CONCAT(REPLACE(REPLACE(REPLACE(?, '!', '!!'), '%', '!%'), '_', '!_'),'%') ESCAPE '!'")
     }
 
     #[test]
@@ -178,7 +356,7 @@ mod tests {
 
     #[test]
     fn pagination() {
-        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let mut builder = RecordingBuilder::<Sqlite>::new("");
         let pagination = Pagination {
             page: 5,
             limit: 10,
@@ -187,6 +365,267 @@ mod tests {
 
         builder.set_pagination(" ORDER BY s.position", pagination);
 
-        assert_eq!(builder.sql(), " ORDER BY s.position LIMIT ? OFFSET ?")
+        assert_eq!(builder.sql(), " ORDER BY s.position LIMIT ? OFFSET ?");
+        assert_eq!(
+            builder.values(),
+            [ValueType::Int(10), ValueType::BigInt(40)]
+        );
+    }
+
+    #[test]
+    fn in_str_array_expanded() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let options = InOptions::new().with_binding(InBinding::Array);
+        builder
+            .in_str_with("AND code IN", &["a", "b"], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND code IN (?,?)")
+    }
+
+    #[test]
+    fn not_in_str_chunked() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let options = InOptions::new().with_chunk_size(2);
+        builder
+            .in_str_with("AND code NOT IN", &["a", "b", "c"], options)
+            .unwrap();
+
+        assert_eq!(builder.sql(), "AND (code NOT IN (?,?) AND code NOT IN (?))")
+    }
+
+    #[test]
+    fn in_empty_match_nothing() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        builder.in_str::<&str>("AND code IN", &[]);
+        builder.in_int(" AND id NOT IN", &[]);
+
+        assert_eq!(builder.sql(), "AND 1=0")
+    }
+
+    #[test]
+    fn is_null() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        builder.and_is_null("d.date_livraison");
+        builder.and_is_not_null("d.code");
+
+        assert_eq!(
+            builder.sql(),
+            " AND d.date_livraison IS NULL AND d.code IS NOT NULL"
+        )
+    }
+
+    #[test]
+    fn nullable() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        builder.push_value(" AND a=", Nullable::<i32>::Absent.into());
+        builder.push_value(" AND b <> ", Nullable::<i32>::Null.into());
+        builder.push_value(" AND c=", Nullable::Value(1).into());
+
+        assert_eq!(builder.sql(), " AND b IS NOT NULL AND c=?")
+    }
+
+    #[test]
+    fn sorted_pagination() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let fields = [SortField::new("date", "s.date").with_nulls(NullsOrder::First)];
+        let sort = Sort::parse("-date", &fields).unwrap();
+        let pagination = Pagination::new().with_page(2).with_limit(10);
+
+        builder.set_sorted_pagination(&sort, pagination);
+
+        assert_eq!(
+            builder.sql(),
+            " ORDER BY s.date DESC NULLS FIRST LIMIT ? OFFSET ?"
+        )
+    }
+
+    #[test]
+    fn quoted_ident() {
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("");
+        let column = Ident::parse("c.order").unwrap();
+
+        builder.push_value(&format!(" AND {}=", builder.quote(&column)), 1.into());
+
+        assert_eq!(builder.sql(), " AND \"c\".\"order\"=?")
+    }
+
+    #[tokio::test]
+    async fn bind_wide_types() {
+        use sqlx::{
+            sqlite::SqlitePoolOptions,
+            types::chrono::{NaiveDate, NaiveTime},
+        };
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("SELECT ");
+        let values: Vec<ValueType> = vec![
+            i64::MAX.into(),
+            2.5f64.into(),
+            NaiveTime::from_hms_opt(8, 30, 0).unwrap().into(),
+            date.and_hms_opt(12, 0, 0).unwrap().and_utc().into(),
+            vec![1u8, 2, 3].into(),
+        ];
+        for (i, value) in values.into_iter().enumerate() {
+            if i > 0 {
+                builder.push(", ");
+            }
+            builder.bind(value);
+        }
+
+        let row: (i64, f64, NaiveTime, String, Vec<u8>) =
+            builder.build_query_as().fetch_one(&pool).await.unwrap();
+
+        assert_eq!(row.0, i64::MAX);
+        assert_eq!(row.1, 2.5);
+        assert_eq!(row.2, NaiveTime::from_hms_opt(8, 30, 0).unwrap());
+        assert!(row.3.starts_with("2024-05-01"));
+        assert_eq!(row.4, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn list() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut builder: QueryBuilder<'_, Sqlite> =
+            QueryBuilder::new("SELECT value FROM json_each('[1,2,3,4]') WHERE 1=1");
+        builder.push_value(" AND value IN", vec![2, 4, 5].into());
+        builder.push_value(" AND value NOT IN", Vec::<i32>::new().into());
+
+        let rows: Vec<(i32,)> = builder.build_query_as().fetch_all(&pool).await.unwrap();
+
+        assert_eq!(rows, vec![(2,), (4,)]);
+    }
+
+    #[tokio::test]
+    async fn search_modes() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE tiers (id INTEGER, name TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO tiers VALUES (1, 'Hélène'), (2, 'HÉLÈNE'), (3, 'Helene'), (4, 'a*b'),
+            (5, 'ŁÓDŹ'), (6, 'Straße'), (7, 'STRASSE')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let search = |value: &'static str, mode: SearchMode| {
+            let pool = pool.clone();
+            async move {
+                let mut builder: QueryBuilder<'_, Sqlite> =
+                    QueryBuilder::new("SELECT id FROM tiers WHERE 1=1");
+                builder.like_within_mode(" AND name", value.into(), mode);
+                builder.push(" ORDER BY id");
+                let rows: Vec<(i32,)> = builder.build_query_as().fetch_all(&pool).await.unwrap();
+                rows.into_iter().map(|x| x.0).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(search("élè", SearchMode::CaseSensitive).await, vec![1]);
+        assert_eq!(search("*", SearchMode::CaseSensitive).await, vec![4]);
+        assert_eq!(search("ÉLÈ", SearchMode::CaseInsensitive).await, vec![1, 2]);
+        assert_eq!(
+            search("hele", SearchMode::AccentInsensitive).await,
+            vec![1, 2, 3]
+        );
+        assert_eq!(search("łódź", SearchMode::CaseInsensitive).await, vec![5]);
+        assert_eq!(search("lodz", SearchMode::AccentInsensitive).await, vec![5]);
+        assert_eq!(
+            search("strasse", SearchMode::AccentInsensitive).await,
+            vec![6, 7]
+        );
+    }
+
+    #[tokio::test]
+    async fn like_escaped() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE refs (id INTEGER, code TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO refs VALUES (1, 'AB_12%'), (2, 'ABX12%'), (3, 'AB_123'), (4, 'x!AB_12%')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let search = |push: fn(&mut QueryBuilder<'_, Sqlite>)| {
+            let pool = pool.clone();
+            async move {
+                let mut builder: QueryBuilder<'_, Sqlite> =
+                    QueryBuilder::new("SELECT id FROM refs WHERE 1=1");
+                push(&mut builder);
+                builder.push(" ORDER BY id");
+                let rows: Vec<(i32,)> = builder.build_query_as().fetch_all(&pool).await.unwrap();
+                rows.into_iter().map(|x| x.0).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            search(|b| b.like_starts_with(" AND code", "AB_12%".into())).await,
+            vec![1]
+        );
+        assert_eq!(
+            search(|b| b.like_within(" AND code", "!AB".into())).await,
+            vec![4]
+        );
+        assert_eq!(
+            search(|b| b.like_ends_with(" AND code", "_12%".into())).await,
+            vec![1, 4]
+        );
+        assert_eq!(
+            search(|b| b.like_exact(" AND code", "ab_12%".into())).await,
+            vec![1]
+        );
+        assert_eq!(
+            search(|b| b.like_raw(" AND code", "AB_12%".into())).await,
+            vec![1, 2, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn bind_raw_query() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        let row: (String, i64, Option<i32>) = sqlx::query_as("SELECT ?, ?, ?")
+            .bind(ValueType::from("a"))
+            .bind(ValueType::BigInt(7))
+            .bind(ValueType::None)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let mut builder: QueryBuilder<'_, Sqlite> = QueryBuilder::new("SELECT ");
+        let mut separated = builder.separated(", ");
+        separated.push_bind(ValueType::Int(1));
+        separated.push_bind(ValueType::Bool(true));
+        let values: (i32, bool) = builder.build_query_as().fetch_one(&pool).await.unwrap();
+
+        assert_eq!(row, ("a".to_string(), 7, None));
+        assert_eq!(values, (1, true));
     }
 }
@@ -1,102 +1,545 @@
-use sqlx::types::chrono::{NaiveDate, NaiveDateTime};
+use std::fmt;
 
-#[derive(Clone)]
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use crate::keyset::encode_hex;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ValueType {
     String(String),
     Int(i32),
+    BigInt(i64),
     Float(f32),
+    Double(f64),
+    /// NUMERIC, lié comme texte sur Sqlite
+    #[cfg(feature = "decimal")]
+    Decimal(rust_decimal::Decimal),
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    /// JSONB sur Postgres
+    #[cfg(feature = "json")]
+    Json(serde_json::Value),
     Date(NaiveDate),
+    Time(NaiveTime),
     DateTime(NaiveDateTime),
+    DateTimeUtc(DateTime<Utc>),
     Bool(bool),
+    Bytes(Vec<u8>),
+    /// liste de valeurs: colonne tableau Postgres (`= ANY`, `@>`, `&&`) ou liste IN.
+    /// Une liste vide est ignorée par push_value, comme ValueType::None
+    List(Vec<ValueType>),
+    /// valeur explicitement nulle: `IS NULL` dans un filtre, `NULL` dans un SET
+    Null,
+    /// valeur absente: le filtre est ignoré
     None,
 }
 
-impl From<Option<String>> for ValueType {
-    fn from(value: Option<String>) -> Self {
+impl ValueType {
+    pub fn list<I>(values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: IntoValueType,
+    {
+        Self::List(values.into_iter().map(|x| x.into_value_type()).collect())
+    }
+}
+
+/// Valeur brute pour les logs: chaînes sans guillemets, dates au format ISO,
+/// NULL pour ValueType::Null et rien pour ValueType::None.
+/// Pour un littéral SQL utilisable dans une requête, voir debug_sql::render_sql
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(x) => f.write_str(x),
+            Self::Int(x) => write!(f, "{x}"),
+            Self::BigInt(x) => write!(f, "{x}"),
+            Self::Float(x) => write!(f, "{x}"),
+            Self::Double(x) => write!(f, "{x}"),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(x) => write!(f, "{x}"),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(x) => write!(f, "{x}"),
+            #[cfg(feature = "json")]
+            Self::Json(x) => write!(f, "{x}"),
+            Self::Date(x) => write!(f, "{}", x.format("%Y-%m-%d")),
+            Self::Time(x) => write!(f, "{}", x.format("%H:%M:%S%.f")),
+            Self::DateTime(x) => write!(f, "{}", x.format("%Y-%m-%d %H:%M:%S%.f")),
+            Self::DateTimeUtc(x) => write!(f, "{}", x.to_rfc3339()),
+            Self::Bool(x) => write!(f, "{x}"),
+            Self::Bytes(x) => write!(f, "0x{}", encode_hex(x)),
+            Self::List(x) => {
+                f.write_str("[")?;
+                for (i, value) in x.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+            Self::Null => f.write_str("NULL"),
+            Self::None => Ok(()),
+        }
+    }
+}
+
+/// Filtre à trois états: non fourni, explicitement nul ou valeur
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Nullable<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> From<Option<Option<T>>> for Nullable<T> {
+    fn from(value: Option<Option<T>>) -> Self {
         match value {
-            Some(x) => Self::String(x),
-            _ => Self::None,
+            Some(Some(x)) => Self::Value(x),
+            Some(None) => Self::Null,
+            None => Self::Absent,
         }
     }
 }
 
-impl From<String> for ValueType {
-    fn from(value: String) -> Self {
-        Self::String(value)
+/// Conversion de n'importe quel champ d'un DTO en ValueType:
+/// `builder.push_value(" AND code=", dto.code.into_value_type())`.
+/// Option<T> et Nullable<T> sont convertis via T, None devient ValueType::None
+pub trait IntoValueType {
+    fn into_value_type(self) -> ValueType;
+}
+
+impl IntoValueType for ValueType {
+    fn into_value_type(self) -> ValueType {
+        self
     }
 }
 
-impl From<Option<&str>> for ValueType {
-    fn from(value: Option<&str>) -> Self {
-        match value {
-            Some(x) => Self::String(x.to_owned()),
-            _ => Self::None,
+impl<T: IntoValueType> IntoValueType for Option<T> {
+    fn into_value_type(self) -> ValueType {
+        match self {
+            Some(x) => x.into_value_type(),
+            None => ValueType::None,
         }
     }
 }
 
-impl From<&str> for ValueType {
-    fn from(value: &str) -> Self {
-        Self::String(value.to_owned())
+impl<T: IntoValueType> IntoValueType for Nullable<T> {
+    fn into_value_type(self) -> ValueType {
+        match self {
+            Nullable::Value(x) => x.into_value_type(),
+            Nullable::Null => ValueType::Null,
+            Nullable::Absent => ValueType::None,
+        }
     }
 }
 
-impl From<Option<i32>> for ValueType {
-    fn from(value: Option<i32>) -> Self {
-        match value {
-            Some(x) => Self::Int(x),
-            _ => Self::None,
+impl<T: IntoValueType + Clone> IntoValueType for &T {
+    fn into_value_type(self) -> ValueType {
+        self.clone().into_value_type()
+    }
+}
+
+impl IntoValueType for &str {
+    fn into_value_type(self) -> ValueType {
+        ValueType::String(self.to_owned())
+    }
+}
+
+impl IntoValueType for u32 {
+    /// BigInt au-delà de i32::MAX
+    fn into_value_type(self) -> ValueType {
+        match i32::try_from(self) {
+            Ok(x) => ValueType::Int(x),
+            Err(_) => ValueType::BigInt(i64::from(self)),
         }
     }
 }
 
-impl From<i32> for ValueType {
-    fn from(value: i32) -> Self {
-        Self::Int(value)
+/// Vec<u8> est converti en ValueType::Bytes, les autres Vec en ValueType::List
+macro_rules! impl_list {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl IntoValueType for Vec<$t> {
+                fn into_value_type(self) -> ValueType {
+                    ValueType::list(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_list!(String, &str, i32, i64, f64, bool, NaiveDate, NaiveDateTime,);
+
+#[cfg(feature = "uuid")]
+impl_list!(uuid::Uuid);
+
+macro_rules! impl_into_value_type {
+    ($($t:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl IntoValueType for $t {
+                fn into_value_type(self) -> ValueType {
+                    ValueType::$variant(self.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_into_value_type!(
+    String => String,
+    i8 => Int,
+    i16 => Int,
+    i32 => Int,
+    u8 => Int,
+    u16 => Int,
+    i64 => BigInt,
+    f32 => Float,
+    f64 => Double,
+    bool => Bool,
+    NaiveDate => Date,
+    NaiveTime => Time,
+    NaiveDateTime => DateTime,
+    DateTime<Utc> => DateTimeUtc,
+    Vec<u8> => Bytes,
+);
+
+#[cfg(feature = "decimal")]
+impl_into_value_type!(rust_decimal::Decimal => Decimal);
+#[cfg(feature = "uuid")]
+impl_into_value_type!(uuid::Uuid => Uuid);
+#[cfg(feature = "json")]
+impl_into_value_type!(serde_json::Value => Json);
+
+/// From<T> et From<Option<T>>, pour écrire `value.into()` là où un ValueType est attendu
+macro_rules! impl_from {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl From<$t> for ValueType {
+                fn from(value: $t) -> Self {
+                    value.into_value_type()
+                }
+            }
+
+            impl From<Option<$t>> for ValueType {
+                fn from(value: Option<$t>) -> Self {
+                    value.into_value_type()
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    String,
+    &String,
+    &str,
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    f32,
+    f64,
+    bool,
+    NaiveDate,
+    NaiveTime,
+    NaiveDateTime,
+    DateTime<Utc>,
+    Vec<u8>,
+    Vec<String>,
+    Vec<&str>,
+    Vec<i32>,
+    Vec<i64>,
+    Vec<f64>,
+    Vec<bool>,
+    Vec<NaiveDate>,
+    Vec<NaiveDateTime>,
+);
+
+#[cfg(feature = "decimal")]
+impl_from!(rust_decimal::Decimal);
+#[cfg(feature = "uuid")]
+impl_from!(uuid::Uuid, Vec<uuid::Uuid>);
+#[cfg(feature = "json")]
+impl_from!(serde_json::Value);
+
+impl<T: IntoValueType> From<Nullable<T>> for ValueType {
+    fn from(value: Nullable<T>) -> Self {
+        value.into_value_type()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ValueTypeError {
+    /// entier non signé supérieur à i64::MAX
+    OutOfRange(u64),
+}
+
+impl fmt::Display for ValueTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange(x) => write!(f, "{x} is out of range for a signed 64-bit integer"),
+        }
     }
 }
 
-impl From<Option<bool>> for ValueType {
-    fn from(value: Option<bool>) -> Self {
-        match value {
-            Some(x) => Self::Bool(x),
-            _ => Self::None,
+impl std::error::Error for ValueTypeError {}
+
+/// Int si la valeur tient sur un i32, BigInt sinon
+impl TryFrom<u64> for ValueType {
+    type Error = ValueTypeError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match (i32::try_from(value), i64::try_from(value)) {
+            (Ok(x), _) => Ok(Self::Int(x)),
+            (_, Ok(x)) => Ok(Self::BigInt(x)),
+            _ => Err(ValueTypeError::OutOfRange(value)),
         }
     }
 }
 
-impl From<bool> for ValueType {
-    fn from(value: bool) -> Self {
-        Self::Bool(value)
+impl TryFrom<usize> for ValueType {
+    type Error = ValueTypeError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Self::try_from(value as u64)
     }
 }
 
-impl From<Option<NaiveDate>> for ValueType {
-    fn from(value: Option<NaiveDate>) -> Self {
-        match value {
-            Some(x) => Self::Date(x),
-            _ => Self::None,
+/// Représentation inférée: nombres, chaînes, booléens, null et tableaux JSON.
+/// Les dates sont sérialisées au format ISO et relues comme des chaînes,
+/// la conversion vers le type de la colonne est faite par filter::FilterField
+#[cfg(feature = "serde")]
+impl serde::Serialize for ValueType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::String(x) => serializer.serialize_str(x),
+            Self::Int(x) => serializer.serialize_i32(*x),
+            Self::BigInt(x) => serializer.serialize_i64(*x),
+            Self::Float(x) => serializer.serialize_f32(*x),
+            Self::Double(x) => serializer.serialize_f64(*x),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(x) => serializer.collect_str(x),
+            #[cfg(feature = "uuid")]
+            Self::Uuid(x) => serializer.collect_str(x),
+            #[cfg(feature = "json")]
+            Self::Json(x) => x.serialize(serializer),
+            Self::Date(x) => serializer.collect_str(&x.format("%Y-%m-%d")),
+            Self::Time(x) => serializer.collect_str(&x.format("%H:%M:%S%.f")),
+            Self::DateTime(x) => serializer.collect_str(&x.format("%Y-%m-%dT%H:%M:%S%.f")),
+            Self::DateTimeUtc(x) => serializer.collect_str(&x.to_rfc3339()),
+            Self::Bool(x) => serializer.serialize_bool(*x),
+            Self::Bytes(x) => serializer.serialize_bytes(x),
+            Self::List(x) => serializer.collect_seq(x),
+            Self::Null | Self::None => serializer.serialize_none(),
         }
     }
 }
 
-impl From<NaiveDate> for ValueType {
-    fn from(value: NaiveDate) -> Self {
-        Self::Date(value)
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ValueType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueTypeVisitor)
     }
 }
 
-impl From<Option<NaiveDateTime>> for ValueType {
-    fn from(value: Option<NaiveDateTime>) -> Self {
-        match value {
-            Some(x) => Self::DateTime(x),
-            _ => Self::None,
+#[cfg(feature = "serde")]
+struct ValueTypeVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for ValueTypeVisitor {
+    type Value = ValueType;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string, number, boolean, null or array")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<ValueType, E> {
+        Ok(ValueType::Bool(value))
+    }
+
+    /// Int si la valeur tient sur un i32, BigInt sinon
+    fn visit_i64<E>(self, value: i64) -> Result<ValueType, E> {
+        Ok(match i32::try_from(value) {
+            Ok(x) => ValueType::Int(x),
+            Err(_) => ValueType::BigInt(value),
+        })
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<ValueType, E> {
+        ValueType::try_from(value).map_err(E::custom)
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<ValueType, E> {
+        Ok(ValueType::Double(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<ValueType, E> {
+        Ok(ValueType::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<ValueType, E> {
+        Ok(ValueType::String(value))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<ValueType, E> {
+        Ok(ValueType::Bytes(value.to_vec()))
+    }
+
+    fn visit_unit<E>(self) -> Result<ValueType, E> {
+        Ok(ValueType::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<ValueType, E> {
+        Ok(ValueType::Null)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<ValueType, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<ValueType, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
         }
+
+        Ok(ValueType::List(values))
+    }
+
+    /// Objet JSON conservé tel quel avec la feature json
+    #[cfg(feature = "json")]
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<ValueType, A::Error> {
+        serde::Deserialize::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+            .map(ValueType::Json)
     }
 }
 
-impl From<NaiveDateTime> for ValueType {
-    fn from(value: NaiveDateTime) -> Self {
-        Self::DateTime(value)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_int(value: ValueType, expected: i32) {
+        assert!(matches!(value, ValueType::Int(x) if x == expected));
+    }
+
+    #[test]
+    fn references() {
+        let code = "abc".to_string();
+
+        assert!(matches!(Some(&code).into_value_type(), ValueType::String(x) if x == "abc"));
+        assert!(matches!(ValueType::from(&code), ValueType::String(x) if x == "abc"));
+        assert!(matches!(
+            Option::<&String>::None.into_value_type(),
+            ValueType::None
+        ));
+    }
+
+    #[test]
+    fn small_integers() {
+        assert_int(ValueType::from(-3i8), -3);
+        assert_int(Some(300i16).into(), 300);
+        assert_int(u16::MAX.into(), 65_535);
+    }
+
+    #[test]
+    fn unsigned_range() {
+        assert_int(42u32.into(), 42);
+        assert!(matches!(
+            ValueType::from(u32::MAX),
+            ValueType::BigInt(x) if x == i64::from(u32::MAX)
+        ));
+        assert!(matches!(
+            ValueType::try_from(u64::MAX),
+            Err(ValueTypeError::OutOfRange(u64::MAX))
+        ));
+        assert!(matches!(ValueType::try_from(7usize), Ok(ValueType::Int(7))));
+    }
+
+    #[test]
+    fn floats() {
+        assert!(matches!(ValueType::from(1.5f32), ValueType::Float(x) if x == 1.5));
+        assert!(matches!(Option::<f32>::None.into(), ValueType::None));
+    }
+
+    #[test]
+    fn nullable() {
+        assert!(matches!(
+            Nullable::Value(Some(2u8)).into_value_type(),
+            ValueType::Int(2)
+        ));
+        assert!(matches!(
+            Nullable::<u8>::Null.into_value_type(),
+            ValueType::Null
+        ));
+    }
+
+    #[test]
+    fn lists() {
+        let codes: Option<Vec<&str>> = Some(vec!["a", "b"]);
+
+        assert!(matches!(codes.into_value_type(), ValueType::List(x) if x.len() == 2));
+        assert!(matches!(ValueType::from(vec![1u8, 2]), ValueType::Bytes(_)));
+        assert!(matches!(Option::<Vec<i32>>::None.into(), ValueType::None));
+    }
+
+    #[test]
+    fn display() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let values = ValueType::List(vec![
+            "O'Neil".into(),
+            12.into(),
+            date.into(),
+            date.and_hms_opt(10, 30, 0).unwrap().into(),
+            vec![0xABu8, 1].into(),
+            ValueType::Null,
+        ]);
+
+        assert_eq!(
+            values.to_string(),
+            "[O'Neil, 12, 2024-05-01, 2024-05-01 10:30:00, 0xab01, NULL]"
+        );
+        assert_eq!(ValueType::None.to_string(), "");
+        assert_eq!(format!("{:?}", ValueType::Int(3)), "Int(3)");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_inferred() {
+        let value: ValueType =
+            serde_json::from_str(r#"[1, 3000000000, 1.5, "a", true, null]"#).unwrap();
+        let ValueType::List(values) = value else {
+            panic!("expected a list");
+        };
+
+        assert!(matches!(values[0], ValueType::Int(1)));
+        assert!(matches!(values[1], ValueType::BigInt(3_000_000_000)));
+        assert!(matches!(values[2], ValueType::Double(x) if x == 1.5));
+        assert!(matches!(&values[3], ValueType::String(x) if x == "a"));
+        assert!(matches!(values[4], ValueType::Bool(true)));
+        assert!(matches!(values[5], ValueType::Null));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_dates() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let values = ValueType::List(vec![
+            date.into(),
+            date.and_hms_opt(10, 30, 0).unwrap().into(),
+            ValueType::None,
+        ]);
+
+        assert_eq!(
+            serde_json::to_string(&values).unwrap(),
+            r#"["2024-05-01","2024-05-01T10:30:00",null]"#
+        );
     }
 }
@@ -0,0 +1,249 @@
+use std::fmt;
+
+use sqlx::{Pool, QueryBuilder};
+
+use crate::{
+    dialect::Dialect,
+    ident::Ident,
+    operations::{push_conditions, SqlOperation},
+    types::ValueType,
+};
+
+#[derive(Debug)]
+pub enum UpdateError {
+    /// toutes les valeurs sont ValueType::None
+    NoValues,
+    /// aucun filtre n'a été écrit: la requête modifierait toute la table
+    NoCondition,
+    Sqlx(sqlx::Error),
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoValues => write!(f, "No value to update"),
+            Self::NoCondition => write!(f, "No condition: update would affect the whole table"),
+            Self::Sqlx(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<sqlx::Error> for UpdateError {
+    fn from(value: sqlx::Error) -> Self {
+        Self::Sqlx(value)
+    }
+}
+
+/// UPDATE partiel (PATCH): seules les valeurs présentes sont écrites dans le SET.
+/// ValueType::None est ignoré, ValueType::Null écrit `col = NULL`
+#[derive(Clone, Debug)]
+pub struct UpdateBuilder {
+    table: Ident,
+    values: Vec<(Ident, ValueType)>,
+}
+
+impl UpdateBuilder {
+    pub fn new(table: Ident, values: Vec<(Ident, ValueType)>) -> Self {
+        Self { table, values }
+    }
+
+    pub fn with_value(mut self, column: Ident, value: ValueType) -> Self {
+        self.values.push((column, value));
+        self
+    }
+
+    pub fn with_null(self, column: Ident) -> Self {
+        self.with_value(column, ValueType::Null)
+    }
+
+    pub fn has_values(&self) -> bool {
+        self.values
+            .iter()
+            .any(|(_, x)| !matches!(x, ValueType::None))
+    }
+
+    /// "UPDATE t SET a = $1, b = NULL WHERE 1=1 AND (1=1" suivi des filtres et de ")",
+    /// écrits comme pour un SELECT: `builder.push_value(" AND id=", id.into())`
+    pub fn build<'a, DB, F>(&self, filters: F) -> Result<QueryBuilder<'a, DB>, UpdateError>
+    where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        let mut builder = QueryBuilder::new("");
+        self.push_update(&mut builder, filters)?;
+
+        Ok(builder)
+    }
+
+    /// # Returns
+    /// nombre de lignes modifiées
+    pub async fn execute<DB, F>(&self, pool: &Pool<DB>, filters: F) -> Result<u64, UpdateError>
+    where
+        DB: Dialect,
+        for<'q> QueryBuilder<'q, DB>: SqlOperation,
+        F: for<'q> FnOnce(&mut QueryBuilder<'q, DB>) + Send,
+    {
+        let mut conn = pool.acquire().await?;
+        let result =
+            DB::execute_with(&mut *conn, |builder| self.push_update(builder, filters)).await?;
+
+        Ok(DB::rows_affected(&result))
+    }
+
+    fn push_update<'a, DB, F>(
+        &self,
+        builder: &mut QueryBuilder<'a, DB>,
+        filters: F,
+    ) -> Result<(), UpdateError>
+    where
+        DB: Dialect,
+        QueryBuilder<'a, DB>: SqlOperation,
+        F: FnOnce(&mut QueryBuilder<'a, DB>),
+    {
+        if !self.has_values() {
+            return Err(UpdateError::NoValues);
+        }
+
+        builder.push(format!("UPDATE {} SET ", self.table.quoted::<DB>()));
+
+        let values = self
+            .values
+            .iter()
+            .filter(|(_, x)| !matches!(x, ValueType::None));
+        for (i, (column, value)) in values.enumerate() {
+            if i > 0 {
+                builder.push(", ");
+            }
+            builder.push(format!("{} = ", column.quoted::<DB>()));
+            SqlOperation::bind(builder, value.clone());
+        }
+
+        if push_conditions(builder, filters) {
+            Ok(())
+        } else {
+            Err(UpdateError::NoCondition)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{sqlite::SqlitePoolOptions, MySql, Postgres, Sqlite};
+
+    use super::*;
+
+    fn builder() -> UpdateBuilder {
+        UpdateBuilder::new(
+            Ident::new("tiers").unwrap(),
+            vec![
+                (Ident::new("name").unwrap(), Some("Hélène").into()),
+                (Ident::new("city").unwrap(), Option::<String>::None.into()),
+            ],
+        )
+        .with_null(Ident::new("delivery_date").unwrap())
+    }
+
+    #[test]
+    fn postgres() {
+        let builder = builder()
+            .build::<Postgres, _>(|b| b.push_value(" AND id=", 42.into()))
+            .unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            "UPDATE \"tiers\" SET \"name\" = $1, \"delivery_date\" = NULL WHERE 1=1 AND (1=1 AND id=$2)"
+        )
+    }
+
+    #[test]
+    fn mysql() {
+        let builder = builder()
+            .build::<MySql, _>(|b| b.in_int(" AND id IN", &[1, 2]))
+            .unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            "UPDATE `tiers` SET `name` = ?, `delivery_date` = NULL WHERE 1=1 AND (1=1 AND id IN (?,?))"
+        )
+    }
+
+    #[test]
+    fn no_values() {
+        let builder = UpdateBuilder::new(
+            Ident::new("tiers").unwrap(),
+            vec![(Ident::new("name").unwrap(), ValueType::None)],
+        );
+        let result = builder.build::<Sqlite, _>(|b| b.push_value(" AND id=", 1.into()));
+
+        assert!(matches!(result, Err(UpdateError::NoValues)))
+    }
+
+    #[test]
+    fn no_condition() {
+        let id: Option<i32> = None;
+        let result = builder().build::<Sqlite, _>(|b| b.push_value(" AND id=", id.into()));
+
+        assert!(matches!(result, Err(UpdateError::NoCondition)))
+    }
+
+    #[test]
+    fn empty_not_in_is_no_condition() {
+        let result = builder().build::<MySql, _>(|b| b.in_str::<&str>(" AND code NOT IN", &[]));
+
+        assert!(matches!(result, Err(UpdateError::NoCondition)))
+    }
+
+    #[test]
+    fn or_is_no_condition() {
+        let result = builder().build::<Sqlite, _>(|b| b.in_int(" OR id NOT IN", &[]));
+        assert!(matches!(result, Err(UpdateError::NoCondition)));
+
+        let result = builder().build::<Sqlite, _>(|b| b.push_value(" OR id=", 1.into()));
+        assert!(matches!(result, Err(UpdateError::NoCondition)));
+
+        let builder = builder()
+            .build::<Sqlite, _>(|b| {
+                b.push_value(" AND code=", "a".into());
+                b.push_value(" OR code=", "b".into());
+            })
+            .unwrap();
+        assert!(builder
+            .sql()
+            .ends_with(" WHERE 1=1 AND (1=1 AND code=? OR code=?)"));
+    }
+
+    #[tokio::test]
+    async fn execute() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE tiers (id INTEGER, name TEXT, city TEXT, delivery_date TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO tiers VALUES (1, 'a', 'Lyon', '2024-01-01'), (2, 'b', NULL, NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let count = builder()
+            .execute(&pool, |b| b.push_value(" AND id=", 1.into()))
+            .await
+            .unwrap();
+        let row: (String, Option<String>, Option<String>) =
+            sqlx::query_as("SELECT name, city, delivery_date FROM tiers WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(row, ("Hélène".to_string(), Some("Lyon".to_string()), None));
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+
+use crate::{dialect::Dialect, ident::Ident};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OnConflictError {
+    /// DO UPDATE sans colonne à mettre à jour
+    NoColumns,
+    /// DO UPDATE sans contrainte cible, refusé par Postgres et Sqlite
+    NoTarget,
+    /// prédicat citant plusieurs colonnes mises à jour, non traduisible sur MySQL
+    PredicateColumns,
+}
+
+impl fmt::Display for OnConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoColumns => write!(f, "No column to update on conflict"),
+            Self::NoTarget => write!(f, "Conflict target required to update on conflict"),
+            Self::PredicateColumns => {
+                write!(
+                    f,
+                    "Update predicate must mention at most one updated column"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OnConflictError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictAction {
+    DoNothing,
+    /// predicate: condition SQL de mise à jour, ex: "tiers.updated_at < EXCLUDED.updated_at".
+    /// Sur MySQL, elle est évaluée pour chaque colonne: `col = IF(predicate, VALUES(col), col)`.
+    /// MySQL applique le SET de gauche à droite: les colonnes citées dans predicate sont
+    /// mises à jour en dernier, et predicate ne peut citer qu'une seule colonne mise à jour
+    Update {
+        columns: Vec<Ident>,
+        predicate: Option<String>,
+    },
+}
+
+/// Clause ON CONFLICT (Postgres, Sqlite) ou ON DUPLICATE KEY UPDATE (MySQL) d'un INSERT
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnConflict {
+    /// colonnes de la contrainte unique, ignorées par MySQL qui utilise toutes les clés uniques
+    target: Vec<Ident>,
+    action: ConflictAction,
+}
+
+impl OnConflict {
+    pub fn do_nothing(target: Vec<Ident>) -> Self {
+        Self {
+            target,
+            action: ConflictAction::DoNothing,
+        }
+    }
+
+    /// target est exigé sur toutes les bases pour que la même requête reste valide partout
+    pub fn do_update(target: Vec<Ident>, columns: Vec<Ident>) -> Result<Self, OnConflictError> {
+        if columns.is_empty() {
+            return Err(OnConflictError::NoColumns);
+        }
+        if target.is_empty() {
+            return Err(OnConflictError::NoTarget);
+        }
+
+        Ok(Self {
+            target,
+            action: ConflictAction::Update {
+                columns,
+                predicate: None,
+            },
+        })
+    }
+
+    pub fn target(&self) -> &[Ident] {
+        &self.target
+    }
+
+    pub fn action(&self) -> &ConflictAction {
+        &self.action
+    }
+
+    /// Sans effet sur DoNothing.
+    /// sql ne doit citer qu'une seule colonne mise à jour, sur toutes les bases
+    /// pour que la même requête reste valide partout
+    pub fn with_predicate(self, sql: &str) -> Result<Self, OnConflictError> {
+        match self.action {
+            ConflictAction::Update { columns, .. } => {
+                if columns.iter().filter(|x| mentions(sql, x.name())).count() > 1 {
+                    return Err(OnConflictError::PredicateColumns);
+                }
+
+                Ok(Self {
+                    action: ConflictAction::Update {
+                        columns,
+                        predicate: Some(sql.to_string()),
+                    },
+                    ..self
+                })
+            }
+            ConflictAction::DoNothing => Ok(self),
+        }
+    }
+
+    /// # Arguments
+    /// * `insert_columns` - colonnes de l'INSERT, utilisées par DoNothing sur MySQL
+    ///   lorsqu'aucune cible n'est fournie
+    pub fn to_sql<DB: Dialect>(&self, insert_columns: &[Ident]) -> String {
+        let quote = |x: &Ident| x.quoted::<DB>();
+
+        if DB::SUPPORTS_ON_CONFLICT {
+            let target = if self.target.is_empty() {
+                String::new()
+            } else {
+                let columns = self.target.iter().map(quote).collect::<Vec<_>>();
+                format!(" ({})", columns.join(","))
+            };
+
+            match &self.action {
+                ConflictAction::DoNothing => format!(" ON CONFLICT{target} DO NOTHING"),
+                ConflictAction::Update { columns, predicate } => {
+                    let set = columns
+                        .iter()
+                        .map(|x| format!("{0} = EXCLUDED.{0}", quote(x)))
+                        .collect::<Vec<_>>();
+                    let predicate = predicate
+                        .as_ref()
+                        .map(|x| format!(" WHERE {x}"))
+                        .unwrap_or_default();
+
+                    format!(
+                        " ON CONFLICT{target} DO UPDATE SET {}{predicate}",
+                        set.join(", ")
+                    )
+                }
+            }
+        } else {
+            match &self.action {
+                ConflictAction::DoNothing => match self.target.first().or(insert_columns.first()) {
+                    Some(x) => format!(" ON DUPLICATE KEY UPDATE {0} = {0}", quote(x)),
+                    None => String::new(),
+                },
+                ConflictAction::Update { columns, predicate } => {
+                    let mut columns = columns.iter().collect::<Vec<_>>();
+                    if let Some(p) = predicate {
+                        columns.sort_by_key(|x| mentions(p, x.name()));
+                    }
+                    let set = columns
+                        .into_iter()
+                        .map(|x| match predicate {
+                            Some(p) => format!("{0} = IF({p}, VALUES({0}), {0})", quote(x)),
+                            None => format!("{0} = VALUES({0})", quote(x)),
+                        })
+                        .collect::<Vec<_>>();
+
+                    format!(" ON DUPLICATE KEY UPDATE {}", set.join(", "))
+                }
+            }
+        }
+    }
+}
+
+/// predicate cite la colonne name, hors préfixe ou suffixe d'un autre identifiant
+fn mentions(predicate: &str, name: &str) -> bool {
+    let is_ident = |x: char| x.is_ascii_alphanumeric() || x == '_' || x == '$';
+    let predicate = predicate.to_ascii_lowercase();
+    let name = name.to_ascii_lowercase();
+
+    predicate.match_indices(&name).any(|(i, _)| {
+        let before = predicate[..i].chars().next_back();
+        let after = predicate[i + name.len()..].chars().next();
+
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{MySql, Postgres};
+
+    use super::*;
+
+    fn idents(names: &[&str]) -> Vec<Ident> {
+        names.iter().map(|x| Ident::new(x).unwrap()).collect()
+    }
+
+    #[test]
+    fn postgres_update() {
+        let on_conflict = OnConflict::do_update(idents(&["code"]), idents(&["name", "city"]))
+            .unwrap()
+            .with_predicate("tiers.name IS DISTINCT FROM EXCLUDED.name")
+            .unwrap();
+
+        assert_eq!(
+            on_conflict.to_sql::<Postgres>(&[]),
+            " ON CONFLICT (\"code\") DO UPDATE SET \"name\" = EXCLUDED.\"name\", \"city\" = EXCLUDED.\"city\" WHERE tiers.name IS DISTINCT FROM EXCLUDED.name"
+        )
+    }
+
+    #[test]
+    fn postgres_do_nothing() {
+        let on_conflict = OnConflict::do_nothing(vec![]);
+
+        assert_eq!(
+            on_conflict.to_sql::<Postgres>(&[]),
+            " ON CONFLICT DO NOTHING"
+        )
+    }
+
+    #[test]
+    fn mysql_update() {
+        let on_conflict = OnConflict::do_update(idents(&["code"]), idents(&["name"])).unwrap();
+
+        assert_eq!(
+            on_conflict.to_sql::<MySql>(&[]),
+            " ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        )
+    }
+
+    #[test]
+    fn mysql_conditional_update() {
+        let on_conflict = OnConflict::do_update(idents(&["code"]), idents(&["name"]))
+            .unwrap()
+            .with_predicate("VALUES(version) > version")
+            .unwrap();
+
+        assert_eq!(
+            on_conflict.to_sql::<MySql>(&[]),
+            " ON DUPLICATE KEY UPDATE `name` = IF(VALUES(version) > version, VALUES(`name`), `name`)"
+        )
+    }
+
+    #[test]
+    fn mysql_do_nothing() {
+        let on_conflict = OnConflict::do_nothing(vec![]);
+
+        assert_eq!(
+            on_conflict.to_sql::<MySql>(&idents(&["code", "name"])),
+            " ON DUPLICATE KEY UPDATE `code` = `code`"
+        )
+    }
+
+    #[test]
+    fn mysql_predicate_columns_last() {
+        let on_conflict = OnConflict::do_update(idents(&["code"]), idents(&["version", "name"]))
+            .unwrap()
+            .with_predicate("VALUES(version) > version")
+            .unwrap();
+
+        assert_eq!(
+            on_conflict.to_sql::<MySql>(&[]),
+            " ON DUPLICATE KEY UPDATE `name` = IF(VALUES(version) > version, VALUES(`name`), `name`), `version` = IF(VALUES(version) > version, VALUES(`version`), `version`)"
+        )
+    }
+
+    #[test]
+    fn invalid_update() {
+        assert_eq!(
+            OnConflict::do_update(idents(&["code"]), vec![]),
+            Err(OnConflictError::NoColumns)
+        );
+        assert_eq!(
+            OnConflict::do_update(vec![], idents(&["name"])),
+            Err(OnConflictError::NoTarget)
+        );
+    }
+
+    #[test]
+    fn invalid_predicate() {
+        let on_conflict =
+            OnConflict::do_update(idents(&["code"]), idents(&["version", "name"])).unwrap();
+
+        assert_eq!(
+            on_conflict.with_predicate("VALUES(version) > version AND name <> VALUES(name)"),
+            Err(OnConflictError::PredicateColumns)
+        );
+    }
+
+    #[test]
+    fn predicate_mentions() {
+        assert!(mentions("VALUES(`Version`) > version", "version"));
+        assert!(!mentions("version_date > NOW()", "version"));
+    }
+}